@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use parking_lot::Mutex;
 use tokio_easy_timer::prelude::*;
@@ -9,7 +9,7 @@ struct Config {
 
 #[tokio::main]
 async fn main() {
-    let mut cheduler = Scheduler::with_tz(chrono::FixedOffset::east(8 * 3600));
+    let mut cheduler = Scheduler::with_tz(chrono::FixedOffset::east_opt(8 * 3600).unwrap());
 
     let config = Arc::new(Mutex::new(Config { id: 1 }));
     cheduler.add_ext(config);
@@ -43,10 +43,7 @@ async fn main() {
                 .repeat_async(2, 3.seconds())
                 .and()
                 // Runs every 90 seconds
-                .since_every(0.minutes(), 3.minutes()) // Runs every 3 minutes starting at 0:00
-                .and()
-                .since_every(1.minutes(), 3.minutes()) // Runs every 3 minutes starting at 1:30
-                .at(30.seconds())
+                .every_duration(Duration::from_secs(90))
                 .run(|config: Data<Arc<Mutex<Config>>>| async move {
                     let mut config = config.lock();
                     config.id += 1;
@@ -2,34 +2,32 @@ use dotenv::dotenv;
 use std::sync::Arc;
 
 use teloxide::{
-    prelude::AutoSend,
-    requests::{Request, Requester, RequesterExt},
+    requests::{Request, Requester},
     types::ChatId,
-    Bot,
+    Bot, RequestError,
 };
 use tokio_easy_timer::prelude::*;
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
-    let bot = Arc::new(Bot::from_env().auto_send());
+    let bot = Arc::new(Bot::from_env());
     let mut scheduler = Scheduler::new();
     scheduler.add_ext(bot);
+    scheduler.on_error(|run, err| eprintln!("{:?} failed: {}", run.name, err));
 
     scheduler
-        .add(
-            AsyncJob::new()
-                .every(10.seconds())
-                .run(|bot: Data<Arc<AutoSend<Bot>>>| async move {
-                    bot.send_message(
-                        ChatId(std::env::var("CHAT_ID").unwrap().parse().unwrap()),
-                        format!("Hi!"),
-                    )
-                    .send()
-                    .await
-                    .unwrap();
-                }),
-        )
+        .add(AsyncJob::new().name("say-hi").every(10.seconds()).run(
+            |bot: Data<Arc<Bot>>| async move {
+                bot.send_message(
+                    ChatId(std::env::var("CHAT_ID").unwrap().parse().unwrap()),
+                    "Hi!",
+                )
+                .send()
+                .await?;
+                Ok::<(), RequestError>(())
+            },
+        ))
         .run_pending()
         .await;
 }
@@ -0,0 +1,91 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+use crate::job::to_chrono;
+
+/// Where the scheduler reads the time from, the jobs still wait on tokio timers
+pub trait Clock: Send + Sync {
+    /// Returns the current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The clock of the system, used unless `Scheduler::set_clock` is called
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock for tests, it starts at a given time and moves along with the tokio clock.
+///
+/// Pause the tokio clock, with `#[tokio::test(start_paused = true)]` or `tokio::time::pause`, and `advance` jumps
+/// from one timer to the next without waiting, every job fires on its exact time on the way.
+///
+/// ### Example
+///
+/// ```rust
+/// # use chrono::{TimeZone, Utc};
+/// # use std::time::Duration;
+/// # use tokio_easy_timer::prelude::*;
+/// # use tokio_easy_timer::MockClock;
+/// # #[tokio::main(flavor = "current_thread", start_paused = true)]
+/// # async fn main() {
+/// // a Monday
+/// let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+/// let mut s = Scheduler::with_tz(Utc);
+/// s.set_clock(clock.clone());
+/// let job = s.add_job(
+///     AsyncJob::new()
+///         .at(Interval::Monday)
+///         .at_time(3, 0, 0)
+///         .run(|| async {}),
+/// );
+/// s.run().await;
+///
+/// clock.advance(Duration::from_secs(15 * 24 * 3600)).await;
+/// let fired: Vec<_> = job.history().iter().map(|r| r.run.scheduled_at).collect();
+/// assert_eq!(
+///     fired,
+///     [1, 8, 15].map(|day| Utc.with_ymd_and_hms(2024, 1, day, 3, 0, 0).unwrap())
+/// );
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct MockClock {
+    /// The time the clock was set to, and the tokio instant it was set at
+    base: Arc<Mutex<(DateTime<Utc>, Instant)>>,
+}
+
+impl MockClock {
+    /// A clock that reads `at` now
+    pub fn new(at: DateTime<Utc>) -> Self {
+        Self {
+            base: Arc::new(Mutex::new((at, Instant::now()))),
+        }
+    }
+
+    /// Make the clock read `at`, the tokio timers are left as they are.
+    ///
+    /// Jobs that are waiting notice the jump once their timer fires, like they do when the machine wakes up from sleep.
+    pub fn set(&self, at: DateTime<Utc>) {
+        *self.base.lock() = (at, Instant::now());
+    }
+
+    /// Let `d` go by, with a paused tokio clock this takes no time.
+    pub async fn advance(&self, d: Duration) {
+        tokio::time::sleep(d).await;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        let (at, instant) = *self.base.lock();
+        at + to_chrono(instant.elapsed())
+    }
+}
@@ -7,6 +7,7 @@ use type_key::TypeKey;
 
 pub trait AsAny: Any {
     fn as_any(&self) -> &dyn Any;
+    #[allow(dead_code)]
     fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
@@ -22,6 +23,8 @@ impl<T: Any + 'static> AsAny for T {
 #[derive(Default, Clone)]
 pub struct Extensions {
     map: Arc<RwLock<HashMap<TypeKey, Box<dyn AsAny + Send + Sync>>>>,
+    /// Looked up when a type is not in `map`
+    parent: Option<Arc<Extensions>>,
 }
 
 impl Extensions {
@@ -34,16 +37,31 @@ impl Extensions {
         self.map.write().insert(key, Box::new(Data::new(data)));
     }
 
+    /// Returns a new map on top of this one, it sees every type of this map unless it inserts its own
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn child(&self) -> Extensions {
+        Extensions {
+            map: Arc::default(),
+            parent: Some(Arc::new(self.clone())),
+        }
+    }
+
     /// this will panic if the required type doesn't exist
     pub(crate) fn get_data<T>(&self) -> Data<T>
+    where
+        T: 'static + Send + Sync,
+    {
+        self.find().unwrap()
+    }
+
+    fn find<T>(&self) -> Option<Data<T>>
     where
         T: 'static + Send + Sync,
     {
         let key = TypeKey::of::<T>();
-        let data = self.map.read();
-        let res = data.get(&key).unwrap();
-        let res = (**res).as_any().downcast_ref::<Data<T>>().unwrap();
-        let res = res.clone();
-        res
+        if let Some(res) = self.map.read().get(&key) {
+            return (**res).as_any().downcast_ref::<Data<T>>().cloned();
+        }
+        self.parent.as_ref()?.find()
     }
 }
@@ -1,4 +1,8 @@
+use crate::job::ScheduleError;
+
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Interval {
     /// The next multiple of `n` seconds since the start of the Unix epoch
     Seconds(u32),
@@ -33,18 +37,65 @@ pub enum Interval {
 }
 
 impl Interval {
-    pub(crate) fn to_sec(&self) -> u64 {
+    /// The length of the interval in seconds, months and years have none
+    pub(crate) fn to_sec(self) -> Option<u64> {
+        match self {
+            Interval::Seconds(x) => Some(x as u64),
+            Interval::Minutes(x) => Some(x as u64 * 60),
+            Interval::Hours(x) => Some(x as u64 * 3600),
+            Interval::Days(x) => Some(x as u64 * 3600 * 24),
+            Interval::Weeks(x) => Some(x as u64 * 3600 * 24 * 7),
+            _ => None,
+        }
+    }
+
+    /// The name of the variant, such as `Hours`
+    pub(crate) fn name(self) -> &'static str {
         match self {
-            Interval::Seconds(x) => *x as u64,
-            Interval::Minutes(x) => *x as u64 * 60,
-            Interval::Hours(x) => *x as u64 * 3600,
-            Interval::Days(x) => *x as u64 * 3600 * 24,
-            Interval::Weeks(x) => *x as u64 * 3600 * 24 * 7,
-            _ => unimplemented!(),
+            Interval::Seconds(_) => "Seconds",
+            Interval::Minutes(_) => "Minutes",
+            Interval::Hours(_) => "Hours",
+            Interval::Days(_) => "Days",
+            Interval::Months(_) => "Months",
+            Interval::Weeks(_) => "Weeks",
+            Interval::Years(_) => "Years",
+            Interval::Monday => "Monday",
+            Interval::Tuesday => "Tuesday",
+            Interval::Wednesday => "Wednesday",
+            Interval::Thursday => "Thursday",
+            Interval::Friday => "Friday",
+            Interval::Saturday => "Saturday",
+            Interval::Sunday => "Sunday",
+            Interval::Weekday => "Weekday",
+        }
+    }
+
+    /// Check the value against the range of its cron field, a `step` such as the `n` of `*/n` can not be 0
+    pub(crate) fn check(self, step: bool) -> Result<(), ScheduleError> {
+        let (value, min, max) = match self {
+            Interval::Seconds(x) | Interval::Minutes(x) => (x, 0, 59),
+            Interval::Hours(x) => (x, 0, 23),
+            Interval::Days(x) => (x, 1, 31),
+            Interval::Months(x) => (x, 1, 12),
+            Interval::Weeks(x) => (x, 1, 7),
+            Interval::Years(x) => (x, 1970, 2100),
+            _ => return Ok(()),
+        };
+        let min = if step { 1 } else { min };
+        if (min..=max).contains(&value) {
+            Ok(())
+        } else {
+            Err(ScheduleError::OutOfRange {
+                unit: self.name(),
+                value,
+                min,
+                max,
+            })
         }
     }
 }
 
+/// Turn numbers into an `Interval`, values out of the range of the cron field are reported by `try_run`
 pub trait TimeUnits: Sized {
     fn seconds(self) -> Interval;
     fn minutes(self) -> Interval;
@@ -82,11 +133,11 @@ impl TimeUnits for u32 {
     ///
     /// # Example
     /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
     /// let a = 1.seconds();
     /// assert_eq!(a, Interval::Seconds(1));
     /// ```
     fn seconds(self) -> Interval {
-        assert!(self < 60);
         Interval::Seconds(self)
     }
 
@@ -94,11 +145,11 @@ impl TimeUnits for u32 {
     ///
     /// # Example
     /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
     /// let a = 1.minutes();
     /// assert_eq!(a, Interval::Minutes(1));
     /// ```
     fn minutes(self) -> Interval {
-        assert!(self < 60);
         Interval::Minutes(self)
     }
 
@@ -106,11 +157,11 @@ impl TimeUnits for u32 {
     ///
     /// # Example
     /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
     /// let a = 1.hours();
     /// assert_eq!(a, Interval::Hours(1));
     /// ```
     fn hours(self) -> Interval {
-        assert!(self < 24);
         Interval::Hours(self)
     }
 
@@ -118,11 +169,11 @@ impl TimeUnits for u32 {
     ///
     /// # Example
     /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
     /// let a = 1.days();
     /// assert_eq!(a, Interval::Days(1));
     /// ```
     fn days(self) -> Interval {
-        assert!(self <= 31);
         Interval::Days(self)
     }
 
@@ -130,11 +181,11 @@ impl TimeUnits for u32 {
     ///
     /// # Example
     /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
     /// let a = 1.months();
     /// assert_eq!(a, Interval::Months(1));
     /// ```
     fn months(self) -> Interval {
-        assert!(self <= 12);
         Interval::Months(self)
     }
 
@@ -142,11 +193,11 @@ impl TimeUnits for u32 {
     ///
     /// # Example
     /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
     /// let a = 1.weeks();
     /// assert_eq!(a, Interval::Weeks(1));
     /// ```
     fn weeks(self) -> Interval {
-        assert!(self <= 7);
         Interval::Weeks(self)
     }
 
@@ -154,11 +205,11 @@ impl TimeUnits for u32 {
     ///
     /// # Example
     /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
     /// let a = 1.years();
     /// assert_eq!(a, Interval::Years(1));
     /// ```
     fn years(self) -> Interval {
-        assert!(self <= 2100);
         Interval::Years(self)
     }
 }
@@ -1,15 +1,38 @@
 mod async_handler;
 mod async_job;
+#[cfg(feature = "serde")]
+mod config;
+mod handle;
+mod hooks;
 mod jobschedule;
+mod options;
+mod run;
+mod runner;
+#[cfg(feature = "serde")]
+mod secs;
+mod spec;
 mod sync_handler;
 mod sync_job;
 pub use self::async_handler::AsyncHandler;
-pub use self::async_job::{AsyncJob, AsyncJobBuilder};
-pub use self::jobschedule::JobScheduleBuilder;
+pub use self::async_job::AsyncJobBuilder;
+#[cfg(feature = "serde")]
+pub(crate) use self::config::read_config;
+pub(crate) use self::handle::Timeline;
+pub use self::handle::{JobHandle, JobId, JobStatus};
+pub(crate) use self::hooks::{BlockingCall, Hooks};
+pub(crate) use self::jobschedule::{to_chrono, upcoming};
+pub use self::jobschedule::{JobSchedule, JobScheduleBuilder, ScheduleError};
+pub(crate) use self::options::Dst;
+pub use self::options::{Backoff, ConcurrencyPolicy, DstPolicy, JobOptions, MisfirePolicy, Retry};
+pub use self::run::{HandlerResult, JobError, Misfire, RunInfo, RunOutcome, RunRecord};
+#[cfg(feature = "serde")]
+pub use self::spec::JobArgs;
+pub use self::spec::{HandlerRegistry, JobSpec, SpecError};
 pub use self::sync_handler::SyncHandler;
-pub use self::sync_job::{SyncJob, SyncJobBuilder};
-use crate::{extensions::Extensions, interval::Interval, prelude::TimeUnits};
+pub use self::sync_job::SyncJobBuilder;
+use crate::{extensions::Extensions, interval::Interval, prelude::TimeUnits, timezone::JobTz};
 use chrono::TimeZone;
+use std::{sync::Arc, time::Duration};
 
 pub trait Job<Tz>
 where
@@ -17,8 +40,12 @@ where
 {
     fn box_clone(&self) -> Box<dyn Job<Tz> + Send>;
 
-    /// Start spawn jobs
-    fn start_schedule(&self, e: Extensions, tz: Tz);
+    fn options(&self) -> &JobOptions;
+
+    fn schedules(&self) -> &[JobSchedule];
+
+    /// Start spawn jobs, every spawned task stops once `handle` is cancelled
+    fn start_schedule(&self, e: Extensions, tz: Tz, handle: JobHandle);
 }
 
 pub trait JobBuilder<Args> {
@@ -37,6 +64,288 @@ pub trait JobBuilder<Args> {
     /// Usually not use it directly, use `at_*` and `since_*` is better
     fn get_mut_cron_builder(&mut self) -> &mut JobScheduleBuilder;
 
+    fn get_mut_options(&mut self) -> &mut JobOptions;
+
+    /// Give the job a name, it shows up in reports and makes the job easier to find
+    fn name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.get_mut_options().name = Some(name.into());
+        self
+    }
+
+    /// Add a tag to the job, jobs sharing a tag can be found and cancelled together
+    fn tag(&mut self, tag: impl Into<String>) -> &mut Self {
+        self.get_mut_options().tags.push(tag.into());
+        self
+    }
+
+    /// Called when the handler returns an error, before the hook set on the scheduler
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::new();
+    /// s.add(
+    ///     AsyncJob::new()
+    ///         .name("sync-users")
+    ///         .every(10.minutes())
+    ///         .on_error(|run, err| eprintln!("{:?} failed at {}: {}", run.name, run.scheduled_at, err))
+    ///         .run(|| async { Err::<(), _>("the server is down") }),
+    /// );
+    /// ```
+    fn on_error<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&RunInfo, &JobError) + Send + Sync + 'static,
+    {
+        self.get_mut_options().on_error = Some(Arc::new(f));
+        self
+    }
+
+    /// Called when the handler panics, with the message of the panic, before the hook set on the scheduler
+    ///
+    /// The panic is caught, the job keeps running on its schedule. Unlike errors, every panic is reported, even when the run is retried.
+    fn on_panic<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&RunInfo, &str) + Send + Sync + 'static,
+    {
+        self.get_mut_options().on_panic = Some(Arc::new(f));
+        self
+    }
+
+    /// Call the handler again when it fails or panics, up to `max_attempts` calls in total, waiting as told by `backoff` in between.
+    ///
+    /// Only the error of the last attempt is passed to the `on_error` hooks.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::new();
+    /// s.add(
+    ///     AsyncJob::new()
+    ///         .every(1.hours())
+    ///         .retry(
+    ///             5,
+    ///             Backoff::Exponential {
+    ///                 base: Duration::from_secs(1),
+    ///                 max: Duration::from_secs(60),
+    ///                 jitter: true,
+    ///             },
+    ///         )
+    ///         .run(|| async { Err::<(), _>("the server is down") }),
+    /// );
+    /// ```
+    fn retry(&mut self, max_attempts: u32, backoff: Backoff) -> &mut Self {
+        self.get_mut_options().retry = Some(Retry {
+            max_attempts,
+            backoff,
+        });
+        self
+    }
+
+    /// Called for every tick that went by while the job could not run, before the hook set on the scheduler
+    fn on_misfire<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&RunInfo, &Misfire) + Send + Sync + 'static,
+    {
+        self.get_mut_options().on_misfire = Some(Arc::new(f));
+        self
+    }
+
+    /// Give up on a call of the handler that takes longer than `timeout`, it is reported to the `on_error` hooks like an error and retried as one.
+    ///
+    /// Async handlers are aborted. Sync handlers run on a blocking thread that can not be interrupted, they are reported as timed out but keep running in the background.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::new();
+    /// s.add(
+    ///     AsyncJob::new()
+    ///         .every(1.minutes())
+    ///         .timeout(Duration::from_secs(10))
+    ///         .on_error(|_, err| assert!(err.is_timeout()))
+    ///         .run(|| async { tokio::time::sleep(Duration::from_secs(60)).await }),
+    /// );
+    /// ```
+    fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.get_mut_options().timeout = Some(timeout);
+        self
+    }
+
+    /// Decide what happens when the job is due while its previous run is still going, the default is `ConcurrencyPolicy::Allow`
+    ///
+    /// A run covers every repeat of one scheduled time.
+    fn concurrency(&mut self, policy: ConcurrencyPolicy) -> &mut Self {
+        self.get_mut_options().concurrency = policy;
+        self
+    }
+
+    /// Decide what happens with the ticks that went by while the runtime was busy or the machine was asleep, the default is `MisfirePolicy::Skip`
+    ///
+    /// Ticks skipped because the job was paused are not misfires.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::new();
+    /// s.add(
+    ///     SyncJob::new()
+    ///         .at(3.hours())
+    ///         .misfire(MisfirePolicy::FireIfWithin(Duration::from_secs(30 * 60)))
+    ///         .on_misfire(|run, misfire| println!("{} was {:?} late", run.scheduled_at, misfire.late_by))
+    ///         .run(|| println!("backup")),
+    /// );
+    /// ```
+    fn misfire(&mut self, policy: MisfirePolicy) -> &mut Self {
+        self.get_mut_options().misfire = policy;
+        self
+    }
+
+    /// Run on the times of a whole cron expression, it can be combined with other times through `and`
+    ///
+    /// Both 5 field crontab lines, where Sunday is 0 or 7, and the 6/7 field syntax of the `cron` crate with seconds and years are accepted.
+    /// Other times of the same schedule, such as `at`, are returned as a `ScheduleError::Conflict` by `try_run`.
+    /// A crontab line restricting both the day of month and the day of week, which crontab runs on either day, is returned as
+    /// a `ScheduleError::InvalidCron`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use chrono::Timelike;
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::new();
+    /// let handle = s.add_job(
+    ///     SyncJob::new()
+    ///         .cron("30 9 * * 1-5")
+    ///         .and()
+    ///         .cron("0 0 12 * * Sat,Sun *")
+    ///         .run(|| println!("stand-up or lunch")),
+    /// );
+    /// let next = handle.next_run().unwrap();
+    /// assert!(next.minute() == 30 || next.hour() == 12);
+    /// ```
+    fn cron(&mut self, expr: &str) -> &mut Self {
+        self.get_mut_cron_builder().cron(expr);
+        self
+    }
+
+    /// Evaluate the schedules of the job in `tz` instead of in the timezone of the scheduler
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use chrono::{FixedOffset, Timelike, Utc};
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::with_tz(Utc);
+    /// let tokyo = s.add_job(
+    ///     AsyncJob::new()
+    ///         .timezone(FixedOffset::east_opt(9 * 3600).unwrap())
+    ///         .at_time(9, 0, 0)
+    ///         .run(|| async {}),
+    /// );
+    /// assert_eq!(tokyo.next_run().unwrap().with_timezone(&Utc).hour(), 0);
+    /// ```
+    fn timezone(&mut self, tz: impl Into<JobTz>) -> &mut Self {
+        self.get_mut_options().timezone = Some(tz.into());
+        self
+    }
+
+    /// Like `timezone`, with a timezone picked at runtime by its name, see `JobTz` for the names it knows.
+    ///
+    /// An unknown name is returned by `try_run`.
+    fn timezone_name(&mut self, name: &str) -> &mut Self {
+        match name.parse::<JobTz>() {
+            Ok(tz) => self.get_mut_options().timezone = Some(tz),
+            Err(err) => {
+                self.get_mut_cron_builder().fail(err);
+            }
+        }
+        self
+    }
+
+    /// Decide what happens with the run times that fall on a daylight saving transition of the timezone
+    ///
+    /// By default a local time that happens twice when the clocks fall back runs once, on the first of them,
+    /// and a local time that is skipped when the clocks spring forward runs as much later as the clocks jumped.
+    /// `RunOnce` and `RunBoth` change the former, `ShiftForward` and `Skip` the latter, call it twice to change both.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use chrono::{FixedOffset, TimeZone, Utc};
+    /// # use tokio_easy_timer::prelude::*;
+    /// # use tokio_easy_timer::{DstPolicy, MockClock};
+    /// # #[cfg(feature = "chrono-tz")]
+    /// # {
+    /// let mut s = Scheduler::with_tz(chrono_tz::Europe::Berlin);
+    /// let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 3, 30, 12, 0, 0).unwrap());
+    /// s.set_clock(clock.clone());
+    /// let shifted = s.add_job(AsyncJob::new().at_time(2, 30, 0).run(|| async {}));
+    /// let skipped = s.add_job(
+    ///     AsyncJob::new()
+    ///         .at_time(2, 30, 0)
+    ///         .dst(DstPolicy::Skip)
+    ///         .dst(DstPolicy::RunBoth)
+    ///         .run(|| async {}),
+    /// );
+    ///
+    /// // the clocks move from 02:00 to 03:00 on March 31
+    /// let summer = FixedOffset::east_opt(2 * 3600).unwrap();
+    /// assert_eq!(shifted.next_run().unwrap(), summer.with_ymd_and_hms(2024, 3, 31, 3, 30, 0).unwrap());
+    /// assert_eq!(skipped.next_run().unwrap(), summer.with_ymd_and_hms(2024, 4, 1, 2, 30, 0).unwrap());
+    ///
+    /// // the clocks move from 03:00 back to 02:00 on October 27
+    /// clock.set(Utc.with_ymd_and_hms(2024, 10, 26, 12, 0, 0).unwrap());
+    /// let once: Vec<_> = shifted.upcoming(2).iter().map(|t| t.to_rfc3339()).collect();
+    /// assert_eq!(once, ["2024-10-27T02:30:00+02:00", "2024-10-28T02:30:00+01:00"]);
+    /// let both: Vec<_> = skipped.upcoming(2).iter().map(|t| t.to_rfc3339()).collect();
+    /// assert_eq!(both, ["2024-10-27T02:30:00+02:00", "2024-10-27T02:30:00+01:00"]);
+    /// # }
+    /// ```
+    fn dst(&mut self, policy: DstPolicy) -> &mut Self {
+        self.get_mut_options().dst.set(policy);
+        self
+    }
+
+    /// Run every `period`, counted from the start of the job rather than lined up with the clock like `every`
+    ///
+    /// The period may be shorter than a second, such as 250ms for a polling job. The first run is one period after the start. When the runtime falls so far behind that the next tick is
+    /// already due, the late ticks go through the `MisfirePolicy`. It can not be combined with other times of
+    /// the same schedule, use `and` to start another one.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use std::time::Duration;
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::new();
+    /// let handle = s.add_job(
+    ///     SyncJob::new()
+    ///         .every_duration(Duration::from_secs(90))
+    ///         .run(|| println!("every 90 seconds")),
+    /// );
+    /// let upcoming = handle.upcoming(2);
+    /// assert_eq!((upcoming[1] - upcoming[0]).num_seconds(), 90);
+    /// ```
+    fn every_duration(&mut self, period: Duration) -> &mut Self {
+        self.get_mut_cron_builder().every_duration(period);
+        self
+    }
+
+    /// Run `period` after the end of the previous run, so the runs of the schedule never overlap
+    ///
+    /// Like `every_duration`, it can not be combined with other times of the same schedule.
+    fn with_fixed_delay(&mut self, period: Duration) -> &mut Self {
+        self.get_mut_cron_builder().with_fixed_delay(period);
+        self
+    }
+
     /// Specify a specific run time, equivalent to cron 'n'
     fn at(&mut self, interval: Interval) -> &mut Self {
         self.get_mut_cron_builder().at(interval);
@@ -56,6 +365,7 @@ pub trait JobBuilder<Args> {
     }
 
     /// Specify a specific run time, equivalent to the corn expression 'm-n'
+    #[allow(clippy::wrong_self_convention)]
     fn from_to(&mut self, start: Interval, end: Interval) -> &mut Self {
         self.get_mut_cron_builder().from_to(start, end);
         self
@@ -72,11 +382,11 @@ pub trait JobBuilder<Args> {
         sec: Option<u32>,
     ) -> &mut Self {
         year.map(|x| self.at((x as u32).year()));
-        month.map(|x| self.at((x as u32).month()));
-        day.map(|x| self.at((x as u32).day()));
-        hour.map(|x| self.at((x as u32).hour()));
-        min.map(|x| self.at((x as u32).minute()));
-        sec.map(|x| self.at((x as u32).second()));
+        month.map(|x| self.at(x.month()));
+        day.map(|x| self.at(x.day()));
+        hour.map(|x| self.at(x.hour()));
+        min.map(|x| self.at(x.minute()));
+        sec.map(|x| self.at(x.second()));
         self
     }
 
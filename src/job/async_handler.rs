@@ -1,8 +1,10 @@
 use crate::extensions::{Data, Extensions};
 use std::{future::Future, pin::Pin};
 
+use super::{HandlerResult, JobError};
+
 pub trait AsyncHandler<Args> {
-    fn call(self, e: &Extensions) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+    fn call(self, e: &Extensions) -> Pin<Box<dyn Future<Output = Result<(), JobError>> + Send>>;
 }
 
 macro_rules! impl_handler {
@@ -10,15 +12,16 @@ macro_rules! impl_handler {
         impl<F, Fut, $($P,)*> AsyncHandler<($(Data<$P>,)*)> for F
         where
             $( $P: Clone + 'static + Send + Sync, )*
-            Fut: Future<Output = ()> + Send + 'static,
+            Fut: Future + Send + 'static,
+            Fut::Output: HandlerResult,
             F: Fn($(Data<$P>,)*) -> Fut + Send + Copy,
         {
-            fn call(self, _e: &Extensions) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+            fn call(self, _e: &Extensions) -> Pin<Box<dyn Future<Output = Result<(), JobError>> + Send>> {
                 let f = self.to_owned();
                 let f = f($(_e.get_data::<$P>(),)*);
                 Box::pin(
                     async {
-                        f.await;
+                        f.await.into_result()
                     }
                 )
         }
@@ -1,24 +1,28 @@
-use std::{marker::PhantomData, time::Duration};
+use std::marker::PhantomData;
 
 use chrono::TimeZone;
 
 use crate::{extensions::Extensions, interval::Interval, scheduler::BoxedJob};
 
 use super::{
-    jobschedule::{JobSchedule, JobScheduleBuilder},
-    AsyncHandler, Job, JobBuilder,
+    jobschedule::{JobSchedule, JobScheduleBuilder, ScheduleError},
+    runner::join,
+    AsyncHandler, Job, JobBuilder, JobHandle, JobOptions,
 };
 
 #[derive(Clone)]
 pub struct AsyncJob<Args, F> {
     pub f: F,
     pub jobschedules: Vec<JobSchedule>,
+    pub options: JobOptions,
     pub _phantom: PhantomData<Args>,
 }
 
 pub struct AsyncJobBuilder<Args> {
     jobschedules: Vec<JobSchedule>,
     builder: JobScheduleBuilder,
+    options: JobOptions,
+    error: Option<ScheduleError>,
     _phantom: PhantomData<Args>,
 }
 
@@ -29,61 +33,24 @@ where
     Tz: TimeZone + Send + Copy + 'static,
     <Tz as TimeZone>::Offset: Send,
 {
-    fn box_clone(&self) -> Box<(dyn Job<Tz> + Send + 'static)> {
+    fn box_clone(&self) -> Box<dyn Job<Tz> + Send + 'static> {
         Box::new((*self).clone())
     }
 
-    fn start_schedule(&self, e: Extensions, tz: Tz) {
+    fn options(&self) -> &JobOptions {
+        &self.options
+    }
+
+    fn schedules(&self) -> &[JobSchedule] {
+        &self.jobschedules
+    }
+
+    fn start_schedule(&self, e: Extensions, tz: Tz, handle: JobHandle) {
+        let f = self.f;
         for schedule in self.jobschedules.iter() {
             // spawn a task for every corn schedule
-            let f = self.f.clone();
-            let e = e.clone();
-            let schedule = schedule.clone();
-            tokio::spawn(async move {
-                // delay
-                let now = chrono::Local::now().with_timezone(&tz);
-                let since = schedule.since;
-                let wait_to = tz
-                    .ymd(since.0, since.1, since.2)
-                    .and_hms(since.3, since.4, since.5);
-                let d = wait_to.timestamp() - now.timestamp();
-                if d > 0 {
-                    tokio::time::sleep(Duration::from_secs(d as u64)).await;
-                }
-
-                // run jobs
-                for next in schedule.schedule.upcoming(tz) {
-                    // Calculates the time left until the next task run
-                    let now = chrono::Local::now().with_timezone(&tz);
-                    let d = next.timestamp() - now.timestamp();
-                    if d < 0 {
-                        continue;
-                    }
-                    let e = e.clone();
-                    // prepare a task for the next job
-                    tokio::spawn(async move {
-                        // Wait until the next job runs
-                        tokio::time::sleep(Duration::from_secs(d as u64)).await;
-
-                        // Handle repeat
-                        for i in 0..schedule.repeat {
-                            if schedule.is_async {
-                                let e = e.clone();
-                                tokio::spawn(async move {
-                                    f.call(&e).await;
-                                });
-                            } else {
-                                f.call(&e).await;
-                            }
-                            if schedule.interval > 0 && i < schedule.repeat - 1 {
-                                tokio::time::sleep(Duration::from_secs(schedule.interval)).await;
-                            }
-                        }
-                    });
-
-                    // wait until this task run
-                    tokio::time::sleep(Duration::from_secs(d as u64)).await;
-                }
+            schedule.start(e.clone(), tz, handle.clone(), move |e| {
+                Box::pin(join(tokio::spawn(f.call(&e))))
             });
         }
     }
@@ -93,21 +60,46 @@ impl<Args> AsyncJobBuilder<Args>
 where
     Args: Clone + 'static + Send + Sync,
 {
-    /// Constructs a new async job
+    /// Constructs a new async job, panics if a schedule is not valid
     pub fn run<Tz, F>(&mut self, f: F) -> BoxedJob<Tz>
+    where
+        F: AsyncHandler<Args> + Send + 'static + Clone + Copy,
+        Tz: TimeZone + Send + Sync + 'static + Clone + Copy,
+        <Tz as TimeZone>::Offset: Send + Sync,
+    {
+        self.try_run(f).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new async job, or returns the first invalid value given to the builder
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// let err = AsyncJob::new()
+    ///     .since_every(1.hours(), 30.minutes())
+    ///     .try_run::<chrono::Local, _>(|| async {})
+    ///     .err()
+    ///     .unwrap();
+    /// assert_eq!(err.to_string(), "since_every with Hours/Minutes mismatch");
+    /// ```
+    pub fn try_run<Tz, F>(&mut self, f: F) -> Result<BoxedJob<Tz>, ScheduleError>
     where
         F: AsyncHandler<Args> + Send + 'static + Clone + Copy,
         Tz: TimeZone + Send + Sync + 'static + Clone + Copy,
         <Tz as TimeZone>::Offset: Send + Sync,
     {
         self.and();
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
         let job: AsyncJob<Args, F> = AsyncJob {
             f: f.to_owned(),
             jobschedules: self.jobschedules.clone(),
+            options: self.options.clone(),
             _phantom: PhantomData,
         };
-        let res = Box::new(job);
-        res
+        Ok(Box::new(job))
     }
 
     // / Constructs a new async job
@@ -133,11 +125,18 @@ impl<Args> JobBuilder<Args> for AsyncJobBuilder<Args> {
             _phantom: PhantomData,
             jobschedules: vec![],
             builder: JobScheduleBuilder::new(),
+            options: JobOptions::default(),
+            error: None,
         }
     }
 
     fn and(&mut self) -> &mut Self {
-        self.jobschedules.push(self.builder.build());
+        match self.builder.try_build() {
+            Ok(schedule) => self.jobschedules.push(schedule),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
         self.builder = JobScheduleBuilder::new();
         self
     }
@@ -146,21 +145,23 @@ impl<Args> JobBuilder<Args> for AsyncJobBuilder<Args> {
         &mut self.builder
     }
 
+    fn get_mut_options(&mut self) -> &mut JobOptions {
+        &mut self.options
+    }
+
     fn get_mut_since(&mut self) -> &mut (i32, u32, u32, u32, u32, u32) {
         &mut self.builder.since
     }
 
     fn repeat_seq(&mut self, n: u32, interval: Interval) -> &mut Self {
         self.builder.is_async = false;
-        self.builder.repeat = n;
-        self.builder.interval = interval.to_sec();
+        self.builder.repeat(n, interval);
         self
     }
 
     fn repeat_async(&mut self, n: u32, interval: Interval) -> &mut Self {
         self.builder.is_async = true;
-        self.builder.repeat = n;
-        self.builder.interval = interval.to_sec();
+        self.builder.repeat(n, interval);
         self
     }
 }
@@ -0,0 +1,43 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use serde::Deserialize;
+
+use super::{JobSpec, SpecError};
+
+/// The content of a config file, every `[[job]]` table is a `JobSpec`
+#[derive(Deserialize)]
+struct Config {
+    #[serde(default, alias = "jobs")]
+    job: Vec<JobSpec>,
+}
+
+/// Read the jobs of a config file, the format is picked by the extension: `toml`, `yaml`, `yml` or `json`
+pub(crate) fn read_config(path: &Path) -> Result<Vec<JobSpec>, SpecError> {
+    let invalid = |message: String| SpecError::Config {
+        path: path.to_path_buf(),
+        message,
+    };
+    let text = fs::read_to_string(path).map_err(|err| invalid(err.to_string()))?;
+    let config: Config = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(|err| invalid(err.to_string()))?,
+        Some("yaml" | "yml") => {
+            // through json, so the policies are written as maps like in the other formats instead of yaml tags
+            let value: serde_json::Value =
+                serde_yaml::from_str(&text).map_err(|err| invalid(err.to_string()))?;
+            serde_json::from_value(value).map_err(|err| invalid(err.to_string()))?
+        }
+        Some("json") => serde_json::from_str(&text).map_err(|err| invalid(err.to_string()))?,
+        _ => {
+            return Err(invalid(
+                "unknown format, use toml, yaml or json".to_string(),
+            ))
+        }
+    };
+
+    // jobs are told apart by their name
+    let mut names = HashSet::new();
+    if let Some(spec) = config.job.iter().find(|spec| !names.insert(&spec.name)) {
+        return Err(invalid(format!("job {} is defined twice", spec.name)));
+    }
+    Ok(config.job)
+}
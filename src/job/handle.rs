@@ -0,0 +1,525 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use chrono::{DateTime, FixedOffset, Utc};
+use parking_lot::Mutex;
+use tokio::sync::{watch, Notify, OwnedSemaphorePermit, Semaphore};
+
+use super::{
+    BlockingCall, ConcurrencyPolicy, Hooks, JobError, JobOptions, Misfire, RunInfo, RunOutcome,
+    RunRecord,
+};
+
+/// How many finished calls are kept in the history of a job
+const HISTORY_LEN: usize = 32;
+
+/// An unique id, every job added to the scheduler gets one
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Copy, Clone)]
+pub struct JobId(u64);
+
+impl JobId {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        JobId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for JobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The state of a job, as seen through its [`JobHandle`]
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum JobStatus {
+    /// The job fires on every scheduled time
+    Running,
+    /// The job is still scheduled, but every tick is skipped until it is resumed
+    Paused,
+    /// The job will never run again
+    Cancelled,
+}
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+struct State {
+    status: JobStatus,
+    /// No new run is armed, but the running ones are left alone
+    draining: bool,
+    /// Bumped by every run of a job with `ConcurrencyPolicy::Replace`, the older runs stop
+    generation: u64,
+}
+
+/// Computes the next `n` run times of a job that was started at the first datetime, the second one is now
+pub(crate) type Timeline =
+    Box<dyn Fn(DateTime<Utc>, DateTime<Utc>, usize) -> Vec<DateTime<FixedOffset>> + Send + Sync>;
+
+struct JobState {
+    id: JobId,
+    options: JobOptions,
+    hooks: Arc<Hooks>,
+    timeline: Timeline,
+    started_at: Mutex<Option<DateTime<Utc>>>,
+    state: watch::Sender<State>,
+    next_run: AtomicU64,
+    runs: Mutex<HashMap<u64, (DateTime<FixedOffset>, u32)>>,
+    idle: Notify,
+    history: Mutex<VecDeque<RunRecord>>,
+    /// How many times the handler panicked
+    panics: AtomicU64,
+    /// Taken by the running run, unless the policy is `ConcurrencyPolicy::Allow`
+    slot: Arc<Semaphore>,
+    /// How many runs wait for the slot, with `ConcurrencyPolicy::Queue`
+    waiting: AtomicUsize,
+}
+
+/// A handle to a job added to the scheduler, it can be cloned and sent to other tasks.
+///
+/// The handle reaches every task the job spawns: the loop of each cron schedule and each pending run.
+#[derive(Clone)]
+pub struct JobHandle {
+    inner: Arc<JobState>,
+}
+
+impl JobHandle {
+    pub(crate) fn new(
+        id: JobId,
+        options: &JobOptions,
+        hooks: Arc<Hooks>,
+        timeline: Timeline,
+    ) -> Self {
+        let (state, _) = watch::channel(State {
+            status: JobStatus::Running,
+            draining: false,
+            generation: 0,
+        });
+        Self {
+            inner: Arc::new(JobState {
+                id,
+                options: options.clone(),
+                hooks,
+                timeline,
+                started_at: Mutex::new(None),
+                state,
+                next_run: AtomicU64::new(0),
+                runs: Mutex::new(HashMap::new()),
+                idle: Notify::new(),
+                history: Mutex::new(VecDeque::with_capacity(HISTORY_LEN)),
+                panics: AtomicU64::new(0),
+                slot: Arc::new(Semaphore::new(1)),
+                waiting: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Returns the id of the job
+    pub fn id(&self) -> JobId {
+        self.inner.id
+    }
+
+    /// Returns the name given to the job with `JobBuilder::name`
+    pub fn name(&self) -> Option<&str> {
+        self.inner.options.name.as_deref()
+    }
+
+    /// Returns the tags given to the job with `JobBuilder::tag`
+    pub fn tags(&self) -> &[String] {
+        &self.inner.options.tags
+    }
+
+    /// Returns `true` if the job was given the tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags().iter().any(|t| t == tag)
+    }
+
+    /// Stop the job for good, pending runs are dropped and running async handlers are aborted.
+    ///
+    /// Sync handlers that are already executing can not be interrupted, but their remaining repeats are dropped.
+    pub fn cancel(&self) {
+        self.inner.state.send_modify(|state| {
+            state.status = JobStatus::Cancelled;
+        });
+    }
+
+    /// Skip every tick until `resume` is called, runs that already started are not affected.
+    pub fn pause(&self) {
+        self.set_status(JobStatus::Running, JobStatus::Paused);
+    }
+
+    /// Resume a paused job, it will run again on its next scheduled time.
+    pub fn resume(&self) {
+        self.set_status(JobStatus::Paused, JobStatus::Running);
+    }
+
+    /// Returns `true` if the job is neither paused nor cancelled
+    pub fn is_running(&self) -> bool {
+        self.status() == JobStatus::Running
+    }
+
+    /// Returns the current status of the job
+    pub fn status(&self) -> JobStatus {
+        self.inner.state.borrow().status
+    }
+
+    /// Returns the next time the job will run, `None` if it will never run again
+    pub fn next_run(&self) -> Option<DateTime<FixedOffset>> {
+        self.upcoming(1).pop()
+    }
+
+    /// Returns the next `n` times the job will run, in the offset of the scheduler timezone
+    ///
+    /// Paused jobs still list their run times, cancelled jobs list none.
+    pub fn upcoming(&self, n: usize) -> Vec<DateTime<FixedOffset>> {
+        let state = *self.inner.state.borrow();
+        if state.status == JobStatus::Cancelled || state.draining {
+            return vec![];
+        }
+        let now = self.now();
+        let started = self.inner.started_at.lock().unwrap_or(now);
+        (self.inner.timeline)(started, now, n)
+    }
+
+    /// Returns how many times the handler panicked
+    pub fn panic_count(&self) -> u64 {
+        self.inner.panics.load(Ordering::Relaxed)
+    }
+
+    /// Returns the runs whose handler is executing right now
+    pub fn in_flight(&self) -> Vec<RunInfo> {
+        let mut runs: Vec<RunInfo> = self
+            .inner
+            .runs
+            .lock()
+            .values()
+            .map(|(scheduled_at, attempt)| self.run_info(*scheduled_at, *attempt))
+            .collect();
+        runs.sort_by_key(|run| run.scheduled_at);
+        runs
+    }
+
+    /// Returns the last finished calls of the handler, oldest first, at most 32 are kept
+    ///
+    /// Every attempt of a retried run has its own record, calls that were aborted by `cancel` are not recorded.
+    pub fn history(&self) -> Vec<RunRecord> {
+        self.inner.history.lock().iter().cloned().collect()
+    }
+
+    fn run_info(&self, scheduled_at: DateTime<FixedOffset>, attempt: u32) -> RunInfo {
+        RunInfo {
+            job: self.id(),
+            name: self.inner.options.name.clone(),
+            scheduled_at,
+            attempt,
+        }
+    }
+
+    pub(crate) fn options(&self) -> &JobOptions {
+        &self.inner.options
+    }
+
+    fn set_status(&self, from: JobStatus, to: JobStatus) {
+        self.inner.state.send_if_modified(|state| {
+            let changed = state.status == from;
+            if changed {
+                state.status = to;
+            }
+            changed
+        });
+    }
+
+    /// Returns `true` if a tick that comes now should start a new run
+    pub(crate) fn is_armed(&self) -> bool {
+        let state = *self.inner.state.borrow();
+        state.status == JobStatus::Running && !state.draining
+    }
+
+    /// Remember when the job was started, its delay counts from here
+    pub(crate) fn mark_started(&self) {
+        *self.inner.started_at.lock() = Some(self.now());
+    }
+
+    /// Returns the time on the clock of the scheduler
+    pub(crate) fn now(&self) -> DateTime<Utc> {
+        self.inner.hooks.clock.read().now()
+    }
+
+    /// Stop arming new runs, the runs that already started are left alone
+    pub(crate) fn drain(&self) {
+        self.inner.state.send_modify(|state| {
+            state.draining = true;
+        });
+    }
+
+    /// Wait until no handler of this job is executing
+    pub(crate) async fn idle(&self) {
+        loop {
+            let idle = self.inner.idle.notified();
+            if self.inner.runs.lock().is_empty() {
+                return;
+            }
+            idle.await;
+        }
+    }
+
+    /// Drive `fut` until it completes or the job is cancelled, returns `None` if it was cancelled.
+    pub(crate) async fn until_cancelled<F: Future>(self, fut: F) -> Option<F::Output> {
+        self.until(fut, |state| state.status == JobStatus::Cancelled)
+            .await
+    }
+
+    /// Like `until_cancelled`, but also stops once the job is drained
+    pub(crate) async fn until_stopped<F: Future>(self, fut: F) -> Option<F::Output> {
+        self.until(fut, |state| {
+            state.status == JobStatus::Cancelled || state.draining
+        })
+        .await
+    }
+
+    async fn until<F: Future>(&self, fut: F, stop: impl Fn(&State) -> bool) -> Option<F::Output> {
+        let mut rx = self.inner.state.subscribe();
+        let stopped = async move {
+            while !stop(&rx.borrow_and_update()) {
+                if rx.changed().await.is_err() {
+                    break;
+                }
+            }
+        };
+        tokio::select! {
+            res = fut => Some(res),
+            _ = stopped => None,
+        }
+    }
+
+    /// Wait until a new run may start according to the concurrency policy, returns `None` if the run is dropped
+    pub(crate) async fn admit(&self) -> Option<Admission> {
+        let slot = self.inner.slot.clone();
+        match self.inner.options.concurrency {
+            ConcurrencyPolicy::Allow => Some(Admission::default()),
+            ConcurrencyPolicy::Skip => slot.try_acquire_owned().ok().map(Admission::from),
+            ConcurrencyPolicy::Queue(n) => {
+                if let Ok(permit) = slot.clone().try_acquire_owned() {
+                    return Some(permit.into());
+                }
+                let waiting = &self.inner.waiting;
+                waiting
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |w| {
+                        (w < n).then(|| w + 1)
+                    })
+                    .ok()?;
+                let _waiting = Waiting(waiting);
+                slot.acquire_owned().await.ok().map(Admission::from)
+            }
+            ConcurrencyPolicy::Replace => {
+                let mut generation = 0;
+                self.inner.state.send_modify(|state| {
+                    state.generation += 1;
+                    generation = state.generation;
+                });
+                Some(Admission {
+                    _permit: None,
+                    generation: Some(generation),
+                })
+            }
+        }
+    }
+
+    /// Drive an admitted run until it completes, is replaced by a newer run or the job is cancelled
+    pub(crate) async fn run_admitted<F: Future>(
+        self,
+        admission: Admission,
+        fut: F,
+    ) -> Option<F::Output> {
+        let generation = admission.generation;
+        let res = self
+            .until(fut, move |state| {
+                state.status == JobStatus::Cancelled
+                    || generation.is_some_and(|g| g != state.generation)
+            })
+            .await;
+        drop(admission);
+        res
+    }
+
+    /// Pass the error of a run to the hooks of the job, then to the hooks of the scheduler
+    pub(crate) fn failed(&self, scheduled_at: DateTime<FixedOffset>, attempt: u32, err: &JobError) {
+        let run = self.run_info(scheduled_at, attempt);
+        if let Some(hook) = &self.inner.options.on_error {
+            hook(&run, err);
+        }
+        if let Some(hook) = self.inner.hooks.on_error.read().as_ref() {
+            hook(&run, err);
+        }
+    }
+
+    /// Count a panic of the handler, then pass its message to the hooks of the job and of the scheduler
+    pub(crate) fn panicked(
+        &self,
+        scheduled_at: DateTime<FixedOffset>,
+        attempt: u32,
+        message: &str,
+    ) {
+        self.inner.panics.fetch_add(1, Ordering::Relaxed);
+        let run = self.run_info(scheduled_at, attempt);
+        if let Some(hook) = &self.inner.options.on_panic {
+            hook(&run, message);
+        }
+        if let Some(hook) = self.inner.hooks.on_panic.read().as_ref() {
+            hook(&run, message);
+        }
+    }
+
+    /// Decide which missed ticks still run with the misfire policy, every one of them is passed to the hooks
+    pub(crate) fn misfired(
+        &self,
+        missed: Vec<DateTime<FixedOffset>>,
+        now: DateTime<Utc>,
+    ) -> Vec<DateTime<FixedOffset>> {
+        let fires = self.inner.options.misfire.fires(&missed, now);
+        let job_hook = self.inner.options.on_misfire.as_ref();
+        let hook = self.inner.hooks.on_misfire.read();
+        let mut fired = vec![];
+        for (scheduled_at, fire) in missed.into_iter().zip(fires) {
+            if job_hook.is_some() || hook.is_some() {
+                let run = self.run_info(scheduled_at, 1);
+                let misfire = Misfire {
+                    late_by: (now - scheduled_at.with_timezone(&Utc))
+                        .to_std()
+                        .unwrap_or_default(),
+                    fired: fire,
+                };
+                job_hook
+                    .into_iter()
+                    .chain(hook.as_ref())
+                    .for_each(|hook| hook(&run, &misfire));
+            }
+            if fire {
+                fired.push(scheduled_at);
+            }
+        }
+        fired
+    }
+
+    /// Returns the scheduled time of the last successful run kept in the store, if the job has a name
+    pub(crate) async fn last_run(&self) -> Option<DateTime<Utc>> {
+        let name = self.name()?.to_string();
+        let store = self.inner.hooks.store.read().clone()?;
+        let _call = self.enter_blocking();
+        tokio::task::spawn_blocking(move || {
+            // a store that can not be read is the same as a fresh one
+            store.last_run(&name).unwrap_or_else(|err| {
+                log::warn!("the last run of job {} is unknown: {}", name, err);
+                None
+            })
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Keep the scheduled time of a successful run in the store, if the job has a name
+    pub(crate) fn succeeded(&self, scheduled_at: DateTime<FixedOffset>) {
+        let (Some(name), Some(store)) = (self.name(), self.inner.hooks.store.read().clone()) else {
+            return;
+        };
+        let name = name.to_string();
+        let at = scheduled_at.with_timezone(&Utc);
+        let call = self.enter_blocking();
+        tokio::task::spawn_blocking(move || {
+            let _call = call;
+            // the run itself went well, a failed write only costs a catch-up on the next start
+            if let Err(err) = store.set_last_run(&name, at) {
+                log::warn!("the last run of job {} is not kept: {}", name, err);
+            }
+        });
+    }
+
+    /// Count a call on the blocking thread pool until the returned guard is dropped
+    pub(crate) fn enter_blocking(&self) -> BlockingCall {
+        self.inner.hooks.blocking.enter()
+    }
+
+    /// Add a finished call of the handler to the history
+    pub(crate) fn record(
+        &self,
+        scheduled_at: DateTime<FixedOffset>,
+        attempt: u32,
+        started_at: DateTime<Utc>,
+        outcome: RunOutcome,
+    ) {
+        let offset = scheduled_at.offset();
+        let record = RunRecord {
+            run: self.run_info(scheduled_at, attempt),
+            started_at: started_at.with_timezone(offset),
+            finished_at: self.now().with_timezone(offset),
+            outcome,
+        };
+        #[cfg(feature = "testing")]
+        if let Some(hook) = self.inner.hooks.on_run.read().as_ref() {
+            hook(&record);
+        }
+        let mut history = self.inner.history.lock();
+        if history.len() == HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(record);
+    }
+
+    /// Drive a call of the handler, it is listed in `in_flight` until it completes or is dropped
+    pub(crate) async fn track<F: Future>(
+        self,
+        scheduled_at: DateTime<FixedOffset>,
+        attempt: u32,
+        fut: F,
+    ) -> F::Output {
+        let key = self.inner.next_run.fetch_add(1, Ordering::Relaxed);
+        self.inner.runs.lock().insert(key, (scheduled_at, attempt));
+        let _guard = RunGuard { handle: self, key };
+        fut.await
+    }
+}
+
+struct RunGuard {
+    handle: JobHandle,
+    key: u64,
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        let mut runs = self.handle.inner.runs.lock();
+        runs.remove(&self.key);
+        if runs.is_empty() {
+            self.handle.inner.idle.notify_waiters();
+        }
+    }
+}
+
+/// Allows a run to start, the next run may take its place once it is dropped
+#[derive(Default)]
+pub(crate) struct Admission {
+    _permit: Option<OwnedSemaphorePermit>,
+    generation: Option<u64>,
+}
+
+impl From<OwnedSemaphorePermit> for Admission {
+    fn from(permit: OwnedSemaphorePermit) -> Self {
+        Self {
+            _permit: Some(permit),
+            generation: None,
+        }
+    }
+}
+
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
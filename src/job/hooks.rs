@@ -0,0 +1,93 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use parking_lot::RwLock;
+use tokio::sync::Notify;
+
+#[cfg(feature = "testing")]
+use super::RunRecord;
+use super::{JobError, Misfire, RunInfo};
+use crate::{
+    clock::{Clock, SystemClock},
+    store::JobStore,
+};
+
+/// Called when a handler returns an error
+pub(crate) type ErrorHook = Arc<dyn Fn(&RunInfo, &JobError) + Send + Sync>;
+
+/// Called when a handler panics, with the message of the panic
+pub(crate) type PanicHook = Arc<dyn Fn(&RunInfo, &str) + Send + Sync>;
+
+/// Called for every tick that went by while the job could not run
+pub(crate) type MisfireHook = Arc<dyn Fn(&RunInfo, &Misfire) + Send + Sync>;
+
+/// Called with every finished call of a handler, the `RecordingScheduler` keeps them
+#[cfg(feature = "testing")]
+pub(crate) type RunHook = Arc<dyn Fn(&RunRecord) + Send + Sync>;
+
+/// The hooks set on the scheduler, every job calls them after its own, the store the jobs remember their last run in,
+/// the clock they read the time from and the count of their calls on the blocking thread pool
+pub(crate) struct Hooks {
+    pub(crate) on_error: RwLock<Option<ErrorHook>>,
+    pub(crate) on_panic: RwLock<Option<PanicHook>>,
+    pub(crate) on_misfire: RwLock<Option<MisfireHook>>,
+    pub(crate) store: RwLock<Option<Arc<dyn JobStore>>>,
+    pub(crate) clock: RwLock<Arc<dyn Clock>>,
+    pub(crate) blocking: Arc<Blocking>,
+    #[cfg(feature = "testing")]
+    pub(crate) on_run: RwLock<Option<RunHook>>,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Self {
+            on_error: RwLock::default(),
+            on_panic: RwLock::default(),
+            on_misfire: RwLock::default(),
+            store: RwLock::default(),
+            clock: RwLock::new(Arc::new(SystemClock)),
+            blocking: Arc::default(),
+            #[cfg(feature = "testing")]
+            on_run: RwLock::default(),
+        }
+    }
+}
+
+/// Counts the sync handlers and store accesses running on the blocking thread pool
+#[derive(Default)]
+pub(crate) struct Blocking {
+    calls: AtomicUsize,
+    done: Notify,
+}
+
+impl Blocking {
+    /// Count a call until the returned guard is dropped
+    pub(crate) fn enter(self: &Arc<Self>) -> BlockingCall {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        BlockingCall(self.clone())
+    }
+
+    /// Wait until no call is left on the blocking thread pool
+    #[cfg(feature = "testing")]
+    pub(crate) async fn idle(&self) {
+        loop {
+            let done = self.done.notified();
+            if self.calls.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            done.await;
+        }
+    }
+}
+
+pub(crate) struct BlockingCall(Arc<Blocking>);
+
+impl Drop for BlockingCall {
+    fn drop(&mut self) {
+        if self.0.calls.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.done.notify_waiters();
+        }
+    }
+}
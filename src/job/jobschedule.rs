@@ -1,24 +1,265 @@
+use super::{Dst, DstPolicy};
 use crate::interval::Interval;
+use chrono::{DateTime, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
 use cron::Schedule;
-use std::str::FromStr;
+use std::{collections::VecDeque, error::Error, fmt, str::FromStr, time::Duration};
+
+/// More hours than any daylight saving shift lasts
+const DST_HOURS: i64 = 3;
+
+/// The longest period of `every_duration` and `with_fixed_delay`, longer ones overflow the timer and the clock
+const MAX_PERIOD: Duration = Duration::from_secs(100 * 366 * 24 * 3600);
 
 #[derive(Clone)]
 pub struct JobSchedule {
     pub since: (i32, u32, u32, u32, u32, u32),
     pub delay: u64,
-    pub schedule: Schedule,
+    pub kind: ScheduleKind,
     pub is_async: bool,
     pub repeat: u32,
     pub interval: u64,
 }
 
+/// How the run times of a schedule are found
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum ScheduleKind {
+    /// On the times matched by a cron expression
+    Cron(Schedule),
+    /// Every period, counted from the start of the job, whatever the runs take
+    FixedRate(Duration),
+    /// A period after the end of the previous run
+    FixedDelay(Duration),
+}
+
+impl JobSchedule {
+    /// The datetime after which the task will start
+    pub(crate) fn since<Tz: TimeZone>(&self, tz: &Tz) -> DateTime<Tz> {
+        let since = self.since;
+        let naive = NaiveDate::from_ymd_opt(since.0, since.1, since.2)
+            .and_then(|date| date.and_hms_opt(since.3, since.4, since.5))
+            .expect("the since of a built schedule is valid");
+        match tz.from_local_datetime(&naive) {
+            LocalResult::Single(t) | LocalResult::Ambiguous(t, _) => t,
+            LocalResult::None => shift_forward(tz, &naive),
+        }
+    }
+
+    /// The upcoming run times, for a job that was started at `started`
+    ///
+    /// Runs with a fixed delay are assumed to take no time.
+    pub(crate) fn upcoming<'a, Tz: TimeZone + 'a>(
+        &'a self,
+        tz: &Tz,
+        dst: Dst,
+        started: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Box<dyn Iterator<Item = DateTime<Tz>> + 'a> {
+        let delayed = started + chrono::Duration::seconds(self.delay as i64);
+        let period = match &self.kind {
+            ScheduleKind::Cron(schedule) => {
+                let after = delayed.max(now).with_timezone(tz).max(self.since(tz));
+                return Box::new(cron_after(schedule, &after, dst));
+            }
+            ScheduleKind::FixedRate(period) | ScheduleKind::FixedDelay(period) => *period,
+        };
+        // the first tick is one period after the wait for `delay` and `since`
+        let first = match delayed.with_timezone(tz).max(self.since(tz)) + to_chrono(period) {
+            first if first >= now => first,
+            first => {
+                let behind = now
+                    .signed_duration_since(first.clone())
+                    .to_std()
+                    .unwrap_or_default();
+                let ticks = behind.as_nanos().div_ceil(period.as_nanos());
+                first + to_chrono(Duration::from_nanos((period.as_nanos() * ticks) as u64))
+            }
+        };
+        Box::new(every(first, period))
+    }
+
+    /// The run times after `last` that are already due at `now`
+    pub(crate) fn missed<Tz: TimeZone>(
+        &self,
+        tz: &Tz,
+        dst: Dst,
+        last: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Vec<DateTime<FixedOffset>> {
+        let after = last.with_timezone(tz).max(self.since(tz));
+        let missed: Box<dyn Iterator<Item = DateTime<Tz>>> = match &self.kind {
+            ScheduleKind::Cron(schedule) => Box::new(cron_after(schedule, &after, dst)),
+            ScheduleKind::FixedRate(period) => Box::new(every(after + to_chrono(*period), *period)),
+            // the next run only starts once the previous one is over
+            ScheduleKind::FixedDelay(period) => {
+                Box::new(every(after + to_chrono(*period), *period).take(1))
+            }
+        };
+        missed
+            .take_while(|t| *t <= now)
+            .map(|t| t.with_timezone(&t.offset().fix()))
+            .collect()
+    }
+}
+
+/// The times matched by `schedule` after `after`, on the wall clock of its timezone.
+///
+/// The `cron` crate leaves out every local time that is skipped or repeated when the clocks change,
+/// so the schedule is matched against local times and those are placed on the timeline as told by `dst`.
+pub(crate) fn cron_after<'a, Tz: TimeZone + 'a>(
+    schedule: &'a Schedule,
+    after: &DateTime<Tz>,
+    dst: Dst,
+) -> impl Iterator<Item = DateTime<Tz>> + 'a {
+    // around a change of the clocks, local times before `after` may still come after it
+    let tz = after.timezone();
+    let offsets: Vec<i32> = [-DST_HOURS, 0, DST_HOURS]
+        .iter()
+        .map(|h| after.naive_utc() + chrono::Duration::hours(*h))
+        .map(|t| tz.offset_from_utc_datetime(&t).fix().local_minus_utc())
+        .collect();
+    let shift = offsets.iter().max().unwrap_or(&0) - offsets.iter().min().unwrap_or(&0);
+    let start = after.naive_local() - chrono::Duration::seconds(shift as i64);
+    CronTimes {
+        tz,
+        local: schedule
+            .after(&Utc.from_utc_datetime(&start))
+            .map(|t| t.naive_utc()),
+        dst,
+        next: None,
+        repeated: VecDeque::new(),
+        last: after.clone(),
+    }
+}
+
+struct CronTimes<Tz: TimeZone, I> {
+    tz: Tz,
+    /// The matched local times, in order
+    local: I,
+    dst: Dst,
+    next: Option<DateTime<Tz>>,
+    /// The second times of the local times that happen twice, they come after the first times of all of them
+    repeated: VecDeque<DateTime<Tz>>,
+    /// The time returned last, or `after`
+    last: DateTime<Tz>,
+}
+
+impl<Tz, I> Iterator for CronTimes<Tz, I>
+where
+    Tz: TimeZone,
+    I: Iterator<Item = NaiveDateTime>,
+{
+    type Item = DateTime<Tz>;
+
+    fn next(&mut self) -> Option<DateTime<Tz>> {
+        loop {
+            while self.next.is_none() {
+                let naive = match self.local.next() {
+                    Some(naive) => naive,
+                    None => break,
+                };
+                self.next = match self.tz.from_local_datetime(&naive) {
+                    LocalResult::Single(t) => Some(t),
+                    LocalResult::Ambiguous(first, second) => {
+                        if self.dst.repeated == DstPolicy::RunBoth {
+                            self.repeated.push_back(second);
+                        }
+                        Some(first)
+                    }
+                    LocalResult::None if self.dst.skipped == DstPolicy::Skip => None,
+                    LocalResult::None => Some(shift_forward(&self.tz, &naive)),
+                };
+            }
+            let t = match (self.next.take(), self.repeated.front()) {
+                (Some(next), Some(repeated)) if *repeated < next => {
+                    self.next = Some(next);
+                    self.repeated.pop_front()?
+                }
+                (Some(next), _) => next,
+                (None, Some(_)) => self.repeated.pop_front()?,
+                (None, None) => return None,
+            };
+            // the times looked back at, and shifted times that were matched again, are left out
+            if t > self.last {
+                self.last = t.clone();
+                return Some(t);
+            }
+        }
+    }
+}
+
+/// Place a local time skipped by the clocks as much later as they jumped
+fn shift_forward<Tz: TimeZone>(tz: &Tz, naive: &NaiveDateTime) -> DateTime<Tz> {
+    // the offset before the jump
+    let before = tz
+        .offset_from_utc_datetime(&(*naive - chrono::Duration::days(1)))
+        .fix();
+    tz.from_utc_datetime(&(*naive - chrono::Duration::seconds(before.local_minus_utc() as i64)))
+}
+
+/// The times `period` apart from `first` on
+fn every<Tz: TimeZone>(
+    first: DateTime<Tz>,
+    period: Duration,
+) -> impl Iterator<Item = DateTime<Tz>> {
+    let period = to_chrono(period);
+    std::iter::successors(Some(first), move |t| t.clone().checked_add_signed(period))
+}
+
+pub(crate) fn to_chrono(d: Duration) -> chrono::Duration {
+    chrono::Duration::from_std(d).unwrap_or(chrono::Duration::MAX)
+}
+
+/// Merge the upcoming run times of every schedule of a job, returns the first `n` of them
+pub(crate) fn upcoming<Tz: TimeZone>(
+    schedules: &[JobSchedule],
+    tz: &Tz,
+    dst: Dst,
+    started: DateTime<Utc>,
+    now: DateTime<Utc>,
+    n: usize,
+) -> Vec<DateTime<Tz>> {
+    let mut times: Vec<DateTime<Tz>> = schedules
+        .iter()
+        .flat_map(|schedule| schedule.upcoming(tz, dst, started, now).take(n))
+        .collect();
+    times.sort();
+    times.truncate(n);
+    times
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct JobScheduleBuilder {
     pub since: (i32, u32, u32, u32, u32, u32),
     pub delay: u64,
     pub cron: Vec<Option<String>>,
+    /// A whole cron expression given with `cron`, it takes the place of `cron`
+    pub raw: Option<String>,
     pub is_async: bool,
     pub repeat: u32,
     pub interval: u64,
+    /// Set by `every_duration`, it takes the place of `cron`
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "super::secs::option", skip_serializing_if = "Option::is_none")
+    )]
+    pub fixed_rate: Option<Duration>,
+    /// Set by `with_fixed_delay`, it takes the place of `cron`
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "super::secs::option", skip_serializing_if = "Option::is_none")
+    )]
+    pub fixed_delay: Option<Duration>,
+    /// The first invalid value given to the builder, it is returned by `try_build`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    error: Option<ScheduleError>,
+}
+
+impl Default for JobScheduleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl JobScheduleBuilder {
@@ -26,10 +267,32 @@ impl JobScheduleBuilder {
         Self {
             since: (0, 1, 1, 0, 0, 0),
             cron: vec![None, None, None, None, None, None, None],
+            raw: None,
             repeat: 1,
             interval: 1,
             is_async: false,
             delay: 0,
+            fixed_rate: None,
+            fixed_delay: None,
+            error: None,
+        }
+    }
+
+    /// Keep the first error, it is returned once the schedule is built
+    pub(crate) fn fail(&mut self, err: ScheduleError) -> &mut Self {
+        self.error.get_or_insert(err);
+        self
+    }
+
+    /// Run `n` times on every tick, `interval` apart
+    pub fn repeat(&mut self, n: u32, interval: Interval) -> &mut Self {
+        match interval.to_sec() {
+            Some(interval) => {
+                self.repeat = n;
+                self.interval = interval;
+                self
+            }
+            None => self.fail(ScheduleError::NoFixedLength(interval)),
         }
     }
 
@@ -38,7 +301,68 @@ impl JobScheduleBuilder {
         self
     }
 
+    /// Use a whole cron expression, either a 5 field crontab line or the 6/7 field syntax of the `cron` crate
+    pub fn cron(&mut self, expr: &str) -> &mut Self {
+        match normalize(expr) {
+            Ok(raw) => {
+                self.raw = Some(raw);
+                self
+            }
+            Err(err) => self.fail(err),
+        }
+    }
+
+    /// Run every `period`, counted from the start of the job, on a `tokio::time::interval`
+    pub fn every_duration(&mut self, period: Duration) -> &mut Self {
+        self.fixed_rate = Some(period);
+        self
+    }
+
+    /// Run `period` after the end of the previous run, on a `tokio::time::interval`
+    pub fn with_fixed_delay(&mut self, period: Duration) -> &mut Self {
+        self.fixed_delay = Some(period);
+        self
+    }
+
+    /// Like `try_build`, but panics if the schedule is not valid
     pub fn build(&mut self) -> JobSchedule {
+        self.try_build().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Build the schedule, or tell which value given to the builder is not valid
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// # use tokio_easy_timer::JobScheduleBuilder;
+    /// let err = JobScheduleBuilder::new()
+    ///     .at(75.minutes())
+    ///     .try_build()
+    ///     .err()
+    ///     .unwrap();
+    /// assert_eq!(err.to_string(), "minutes(75) out of range 0..59");
+    /// ```
+    pub fn try_build(&mut self) -> Result<JobSchedule, ScheduleError> {
+        if let Some(err) = &self.error {
+            return Err(err.clone());
+        }
+        let (y, m, d, h, min, s) = self.since;
+        if NaiveDate::from_ymd_opt(y, m, d)
+            .and_then(|date| date.and_hms_opt(h, min, s))
+            .is_none()
+        {
+            return Err(ScheduleError::InvalidSince(self.since));
+        }
+        if let Some(kind) = self.fixed()? {
+            return Ok(self.with_kind(kind));
+        }
+        if let Some(raw) = &self.raw {
+            if self.cron.iter().any(Option::is_some) {
+                return Err(ScheduleError::Conflict { method: "cron" });
+            }
+            return self.with_schedule(raw);
+        }
         for i in 0..6 {
             if self.cron[i].is_some() && self.cron[i + 1].is_none() {
                 self.cron[i + 1] = Some("*".to_string())
@@ -50,19 +374,51 @@ impl JobScheduleBuilder {
             .enumerate()
             .map(|(i, x)| {
                 // x.as_deref().unwrap_or("0")
-                x.as_deref().unwrap_or_else(|| match i {
-                    0 | 1 | 2 => "0",
+                x.as_deref().unwrap_or(match i {
+                    0..=2 => "0",
                     _ => "*",
                 })
             })
             .collect::<Vec<&str>>()
             .join(" ");
 
-        let s = Schedule::from_str(s.as_str())
-            .expect(&format!("cron expression is not valid: {}", s.as_str()));
+        self.with_schedule(&s)
+    }
+
+    /// The interval of `every_duration` or `with_fixed_delay`, if one was given
+    fn fixed(&self) -> Result<Option<ScheduleKind>, ScheduleError> {
+        let (method, kind, period) = match (self.fixed_rate, self.fixed_delay) {
+            (None, None) => return Ok(None),
+            (Some(period), None) => ("every_duration", ScheduleKind::FixedRate(period), period),
+            (_, Some(period)) => ("with_fixed_delay", ScheduleKind::FixedDelay(period), period),
+        };
+        if period.is_zero() {
+            return Err(ScheduleError::ZeroDuration { method });
+        }
+        if period > MAX_PERIOD {
+            return Err(ScheduleError::DurationTooLong { method });
+        }
+        if self.fixed_rate.is_some() && self.fixed_delay.is_some()
+            || self.raw.is_some()
+            || self.cron.iter().any(Option::is_some)
+        {
+            return Err(ScheduleError::Conflict { method });
+        }
+        Ok(Some(kind))
+    }
+
+    fn with_schedule(&self, s: &str) -> Result<JobSchedule, ScheduleError> {
+        let schedule = Schedule::from_str(s).map_err(|err| ScheduleError::InvalidCron {
+            expr: s.to_string(),
+            message: err.to_string(),
+        })?;
+        Ok(self.with_kind(ScheduleKind::Cron(schedule)))
+    }
+
+    fn with_kind(&self, kind: ScheduleKind) -> JobSchedule {
         JobSchedule {
-            schedule: s,
-            repeat: self.repeat as u32,
+            kind,
+            repeat: self.repeat,
             interval: self.interval,
             since: self.since,
             is_async: self.is_async,
@@ -71,11 +427,162 @@ impl JobScheduleBuilder {
     }
 }
 
+/// Why a schedule could not be built
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScheduleError {
+    /// The value is out of the range of its cron field, the range is inclusive
+    OutOfRange {
+        unit: &'static str,
+        value: u32,
+        min: u32,
+        max: u32,
+    },
+    /// A method taking two intervals was given intervals of different units
+    Mismatch {
+        method: &'static str,
+        start: &'static str,
+        other: &'static str,
+    },
+    /// A repeat interval has no fixed length, such as months
+    NoFixedLength(Interval),
+    /// The datetime the job starts after does not exist
+    InvalidSince((i32, u32, u32, u32, u32, u32)),
+    /// The cron expression can not be parsed
+    InvalidCron { expr: String, message: String },
+    /// An interval schedule was given a zero period
+    ZeroDuration { method: &'static str },
+    /// An interval schedule was given a period longer than 100 years, the timer can not count that far
+    DurationTooLong { method: &'static str },
+    /// An interval schedule or a whole cron expression was given other times, they belong to another schedule started with `and`
+    Conflict { method: &'static str },
+    /// The name is not a timezone known to `JobTz`
+    UnknownTimezone(String),
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::OutOfRange {
+                unit,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "{}({}) out of range {}..{}",
+                unit.to_lowercase(),
+                value,
+                min,
+                max
+            ),
+            ScheduleError::Mismatch {
+                method,
+                start,
+                other,
+            } => write!(f, "{} with {}/{} mismatch", method, start, other),
+            ScheduleError::NoFixedLength(interval) => {
+                write!(f, "repeat interval {:?} has no fixed length", interval)
+            }
+            ScheduleError::InvalidSince((y, m, d, h, min, s)) => write!(
+                f,
+                "since {:04}-{:02}-{:02} {:02}:{:02}:{:02} is not a valid datetime",
+                y, m, d, h, min, s
+            ),
+            ScheduleError::InvalidCron { expr, message } => {
+                write!(f, "invalid cron expression {}: {}", expr, message)
+            }
+            ScheduleError::ZeroDuration { method } => write!(f, "{} with a zero duration", method),
+            ScheduleError::DurationTooLong { method } => {
+                write!(f, "{} with a duration longer than 100 years", method)
+            }
+            #[cfg(feature = "chrono-tz")]
+            ScheduleError::UnknownTimezone(name) => write!(f, "unknown timezone {}", name),
+            #[cfg(not(feature = "chrono-tz"))]
+            ScheduleError::UnknownTimezone(name) => write!(
+                f,
+                "unknown timezone {}, IANA names need the chrono-tz feature",
+                name
+            ),
+            ScheduleError::Conflict { method } => write!(
+                f,
+                "{} can not be combined with other times of the same schedule",
+                method
+            ),
+        }
+    }
+}
+
+impl Error for ScheduleError {}
+
+/// Turn a 5 field crontab line into the 6 field syntax of the `cron` crate, other expressions are kept as they are.
+///
+/// Crontab runs on either the day of month or the day of week when both are restricted, the `cron` crate only on days
+/// matching both, such a line is refused.
+pub(crate) fn normalize(expr: &str) -> Result<String, ScheduleError> {
+    let fields: Vec<&str> = expr.split_whitespace().collect();
+    if fields.len() != 5 {
+        return Ok(fields.join(" "));
+    }
+    let unrestricted = |field: &str| field.starts_with('*') || field == "?";
+    if !unrestricted(fields[2]) && !unrestricted(fields[4]) {
+        return Err(ScheduleError::InvalidCron {
+            expr: expr.to_string(),
+            message:
+                "crontab runs on either the day of month or the day of week, give one of them as *"
+                    .to_string(),
+        });
+    }
+    Ok(format!(
+        "0 {} {}",
+        fields[..4].join(" "),
+        weekdays(fields[4])
+    ))
+}
+
+/// Crontab counts the weekdays from 0 or 7 for Sunday, the `cron` crate from 1 for Sunday.
+///
+/// Numeric ranges are listed day by day, a range ending on Sunday can not be shifted into the `cron` numbering.
+fn weekdays(field: &str) -> String {
+    let day = |d: &str| d.parse::<u32>().ok().filter(|d| *d <= 7);
+    field
+        .split(',')
+        .map(|item| {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, step.parse::<usize>().ok().filter(|s| *s > 0)),
+                None => (item, Some(1)),
+            };
+            let days = match range.split_once('-') {
+                Some((a, b)) => day(a).zip(day(b)),
+                // a single day with a step runs up to the end of the week
+                None if item.contains('/') => day(range).map(|a| (a, 7)),
+                None => day(range).map(|a| (a, a)),
+            };
+            match (days, step) {
+                (Some((a, b)), Some(step)) if a <= b => {
+                    let mut days: Vec<u32> = (a..=b).step_by(step).map(|d| d % 7 + 1).collect();
+                    days.sort_unstable();
+                    days.dedup();
+                    days.iter()
+                        .map(|d| d.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                }
+                // names, `*` and malformed items are left to the `cron` crate
+                _ => item.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 macro_rules! every_start {
     ($( {$Varient: ident, $Index: expr} ),* | ($WeekIndex:expr) | $({$WeekVarient: ident, $I: expr} ),* ) => {
 
         impl JobScheduleBuilder {
             pub fn at(&mut self, interval: Interval) -> &mut Self {
+                if let Err(err) = interval.check(false) {
+                    return self.fail(err);
+                }
                 match interval {
                     $(
                         Interval::$Varient(x) => {
@@ -103,6 +610,9 @@ macro_rules! every_start {
             }
 
             pub fn since_every(&mut self, start: Interval, interval: Interval) -> &mut Self {
+                if let Err(err) = start.check(false).and(interval.check(true)) {
+                    return self.fail(err);
+                }
                 match (start, interval) {
                     $(
                         (Interval::$Varient(start), Interval::$Varient(interval)) => {
@@ -113,12 +623,21 @@ macro_rules! every_start {
                             }
                         }
                     )*
-                    _ => unimplemented!(),
+                    _ => {
+                        return self.fail(ScheduleError::Mismatch {
+                            method: "since_every",
+                            start: start.name(),
+                            other: interval.name(),
+                        })
+                    }
                 }
                 self
             }
 
             pub fn every(&mut self, interval: Interval) -> &mut Self {
+                if let Err(err) = interval.check(true) {
+                    return self.fail(err);
+                }
                 match interval {
                     $(
                         Interval::$Varient(x) => {
@@ -146,7 +665,11 @@ macro_rules! every_start {
                 self
             }
 
+            #[allow(clippy::wrong_self_convention)]
             pub fn from_to(&mut self, start: Interval, end: Interval) -> &mut Self {
+                if let Err(err) = start.check(false).and(end.check(false)) {
+                    return self.fail(err);
+                }
                 match (start, end) {
                     $(
                         (Interval::$Varient(start), Interval::$Varient(end)) => {
@@ -157,7 +680,13 @@ macro_rules! every_start {
                             }
                         }
                     )*
-                    _ => unimplemented!(),
+                    _ => {
+                        return self.fail(ScheduleError::Mismatch {
+                            method: "from_to",
+                            start: start.name(),
+                            other: end.name(),
+                        })
+                    }
                 }
                 self
             }
@@ -0,0 +1,184 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use chrono::{DateTime, FixedOffset, Utc};
+
+use super::hooks::{ErrorHook, MisfireHook, PanicHook};
+use crate::timezone::JobTz;
+
+/// What to do when a job is due while its previous run is still going
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ConcurrencyPolicy {
+    /// Start the new run next to the old one, runs may overlap without limit
+    #[default]
+    Allow,
+    /// Drop the tick, the old run keeps going
+    Skip,
+    /// Run one at a time, up to `n` runs wait for their turn and the rest are dropped
+    Queue(usize),
+    /// Abort the old run and start the new one
+    Replace,
+}
+
+/// What to do with the ticks that went by while the job could not run, because the runtime was busy or the machine was asleep
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MisfirePolicy {
+    /// Drop the missed ticks
+    #[default]
+    Skip,
+    /// Run once for the latest missed tick
+    FireOnce,
+    /// Run for every missed tick, but at most for the latest `n` of them
+    FireAll(usize),
+    /// Like `FireOnce`, but only if the latest missed tick is late by no more than the grace time
+    FireIfWithin(#[cfg_attr(feature = "serde", serde(with = "super::secs"))] Duration),
+}
+
+impl MisfirePolicy {
+    /// Decide which of the missed ticks run, `missed` is sorted from the oldest tick
+    pub(crate) fn fires(&self, missed: &[DateTime<FixedOffset>], now: DateTime<Utc>) -> Vec<bool> {
+        let n = missed.len();
+        let fire_last = |k: usize| (0..n).map(|i| i + k >= n).collect();
+        match *self {
+            MisfirePolicy::Skip => vec![false; n],
+            MisfirePolicy::FireOnce => fire_last(1),
+            MisfirePolicy::FireAll(cap) => fire_last(cap),
+            MisfirePolicy::FireIfWithin(grace) => {
+                let within = missed
+                    .last()
+                    .and_then(|latest| (now - latest.with_timezone(&Utc)).to_std().ok())
+                    .is_some_and(|late| late <= grace);
+                fire_last(within as usize)
+            }
+        }
+    }
+}
+
+/// What to do with a run time whose local time is skipped or repeated when the clocks change for daylight saving, see `JobBuilder::dst`
+///
+/// `RunOnce` and `RunBoth` are about the local times that happen twice when the clocks fall back,
+/// `ShiftForward` and `Skip` about the local times that do not happen when the clocks spring forward.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DstPolicy {
+    /// Run on the first of the two times only, the default
+    RunOnce,
+    /// Run on both times, an hour apart when the clocks fall back by an hour
+    RunBoth,
+    /// Run as much later as the clocks jumped, 02:30 runs at 03:30 when the clocks move from 02:00 to 03:00, the default
+    ShiftForward,
+    /// Do not run that day
+    Skip,
+}
+
+/// The `DstPolicy` for each kind of daylight saving transition
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Dst {
+    /// For the local times that happen twice, `RunOnce` or `RunBoth`
+    pub(crate) repeated: DstPolicy,
+    /// For the local times that do not happen, `ShiftForward` or `Skip`
+    pub(crate) skipped: DstPolicy,
+}
+
+impl Dst {
+    /// Use `policy` for the kind of transition it is about
+    pub(crate) fn set(&mut self, policy: DstPolicy) {
+        match policy {
+            DstPolicy::RunOnce | DstPolicy::RunBoth => self.repeated = policy,
+            DstPolicy::ShiftForward | DstPolicy::Skip => self.skipped = policy,
+        }
+    }
+}
+
+impl Default for Dst {
+    fn default() -> Self {
+        Self {
+            repeated: DstPolicy::RunOnce,
+            skipped: DstPolicy::ShiftForward,
+        }
+    }
+}
+
+/// How long to wait before the next attempt of a failed run
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Backoff {
+    /// Always wait the same time
+    Fixed(#[cfg_attr(feature = "serde", serde(with = "super::secs"))] Duration),
+    /// Wait `base`, then double the wait after every attempt, but never more than `max`.
+    ///
+    /// With `jitter`, each wait is picked randomly between half and the full time, so failed jobs do not retry in lockstep.
+    Exponential {
+        #[cfg_attr(feature = "serde", serde(with = "super::secs"))]
+        base: Duration,
+        #[cfg_attr(feature = "serde", serde(with = "super::secs"))]
+        max: Duration,
+        #[cfg_attr(feature = "serde", serde(default))]
+        jitter: bool,
+    },
+}
+
+impl Backoff {
+    /// The wait after the `attempt`-th attempt failed, starting from 1
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        match *self {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { base, max, jitter } => {
+                let factor = 1u32
+                    .checked_shl(attempt.saturating_sub(1))
+                    .unwrap_or(u32::MAX);
+                let delay = base.checked_mul(factor).unwrap_or(max).min(max);
+                if jitter {
+                    let random = RandomState::new().build_hasher().finish();
+                    let half = delay / 2;
+                    half + half.mul_f64((random % 1000) as f64 / 1000.0)
+                } else {
+                    delay
+                }
+            }
+        }
+    }
+}
+
+/// Retry a failed run up to `max_attempts` times in total, see `JobBuilder::retry`
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Retry {
+    pub max_attempts: u32,
+    pub backoff: Backoff,
+}
+
+/// Options shared by every kind of job, they are set through the `JobBuilder`
+///
+/// With the `serde` feature they can be (de)serialized, except for the hooks.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct JobOptions {
+    pub(crate) name: Option<String>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) concurrency: ConcurrencyPolicy,
+    pub(crate) retry: Option<Retry>,
+    #[cfg_attr(feature = "serde", serde(with = "super::secs::option"))]
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) misfire: MisfirePolicy,
+    /// Evaluate the schedules here instead of in the timezone of the scheduler
+    pub(crate) timezone: Option<JobTz>,
+    pub(crate) dst: Dst,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) on_error: Option<ErrorHook>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) on_panic: Option<PanicHook>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) on_misfire: Option<MisfireHook>,
+}
@@ -0,0 +1,127 @@
+use std::{error::Error, fmt, time::Duration};
+
+use chrono::{DateTime, FixedOffset};
+
+use super::JobId;
+
+/// Describes a single run of a job
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunInfo {
+    /// The job this run belongs to
+    pub job: JobId,
+    /// The name of the job, if it was given one
+    pub name: Option<String>,
+    /// The time this run was scheduled at, in the offset of the scheduler timezone
+    pub scheduled_at: DateTime<FixedOffset>,
+    /// Which attempt this is, starting from 1, it only grows when the job retries
+    pub attempt: u32,
+}
+
+/// The error a handler failed with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobError {
+    message: String,
+    timed_out: bool,
+}
+
+impl JobError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            timed_out: false,
+        }
+    }
+
+    pub(crate) fn timed_out(timeout: Duration) -> Self {
+        Self {
+            message: format!("timed out after {:?}", timeout),
+            timed_out: true,
+        }
+    }
+
+    /// Returns the message of the error
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns `true` if the handler did not complete within the timeout of the job
+    pub fn is_timeout(&self) -> bool {
+        self.timed_out
+    }
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for JobError {}
+
+/// How a single call of the handler ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// The handler completed
+    Success,
+    /// The handler returned an error
+    Failed(JobError),
+    /// The handler panicked, with the message of the panic
+    Panicked(String),
+    /// The handler did not complete within the timeout of the job
+    TimedOut(Duration),
+}
+
+impl RunOutcome {
+    /// Returns `true` if the handler completed
+    pub fn is_success(&self) -> bool {
+        *self == RunOutcome::Success
+    }
+}
+
+impl From<Result<(), JobError>> for RunOutcome {
+    fn from(res: Result<(), JobError>) -> Self {
+        match res {
+            Ok(()) => RunOutcome::Success,
+            Err(err) => RunOutcome::Failed(err),
+        }
+    }
+}
+
+/// A tick that went by while the job could not run, see `MisfirePolicy`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misfire {
+    /// How late the tick was when the job caught up
+    pub late_by: Duration,
+    /// Whether the job still runs for this tick
+    pub fired: bool,
+}
+
+/// A finished call of the handler, kept in the history of the job
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunRecord {
+    /// The run the call belongs to
+    pub run: RunInfo,
+    /// When the handler was called
+    pub started_at: DateTime<FixedOffset>,
+    /// When the handler completed, failed or was given up on
+    pub finished_at: DateTime<FixedOffset>,
+    /// How the call ended
+    pub outcome: RunOutcome,
+}
+
+/// What a handler can return, either `()` or `Result<(), E>` where `E` implements `Display`
+pub trait HandlerResult {
+    fn into_result(self) -> Result<(), JobError>;
+}
+
+impl HandlerResult for () {
+    fn into_result(self) -> Result<(), JobError> {
+        Ok(())
+    }
+}
+
+impl<E: fmt::Display> HandlerResult for Result<(), E> {
+    fn into_result(self) -> Result<(), JobError> {
+        self.map_err(|e| JobError::new(e.to_string()))
+    }
+}
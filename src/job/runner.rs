@@ -0,0 +1,365 @@
+use std::{future::Future, pin::Pin, time::Duration};
+
+use chrono::{DateTime, FixedOffset, Offset, TimeZone, Utc};
+use cron::Schedule;
+use tokio::{
+    task::JoinHandle,
+    time::{Instant, MissedTickBehavior},
+};
+
+use crate::extensions::Extensions;
+
+use super::{
+    jobschedule::{cron_after, to_chrono, JobSchedule, ScheduleKind},
+    JobError, JobHandle, RunOutcome,
+};
+
+/// How far the clock may move past the timer before the cron ticks in between count as misfires, such as after the
+/// machine slept through them
+const LATE: Duration = Duration::from_secs(1);
+
+/// A single call of the job handler
+pub(crate) type BoxedRun = Pin<Box<dyn Future<Output = RunOutcome> + Send>>;
+
+impl JobSchedule {
+    /// Spawn the task driving this schedule, every run is started with `run`.
+    ///
+    /// The schedule is evaluated in `tz`, unless the job was given its own timezone.
+    pub(crate) fn start<Tz, R>(&self, e: Extensions, tz: Tz, handle: JobHandle, run: R)
+    where
+        Tz: TimeZone + Send + Copy + 'static,
+        <Tz as TimeZone>::Offset: Send,
+        R: Fn(Extensions) -> BoxedRun + Send + Clone + 'static,
+    {
+        match handle.options().timezone {
+            Some(zone) => self.start_in(e, zone, handle, run),
+            None => self.start_in(e, tz, handle, run),
+        }
+    }
+
+    fn start_in<Tz, R>(&self, e: Extensions, tz: Tz, handle: JobHandle, run: R)
+    where
+        Tz: TimeZone + Send + Copy + 'static,
+        <Tz as TimeZone>::Offset: Send,
+        R: Fn(Extensions) -> BoxedRun + Send + Clone + 'static,
+    {
+        let schedule = self.clone();
+        tokio::spawn(handle.clone().until_stopped(async move {
+            // catch up the runs missed while the process was down
+            if let Some(last) = handle.last_run().await {
+                let now = handle.now();
+                let missed = schedule.missed(&tz, handle.options().dst, last, now);
+                if !missed.is_empty() && handle.is_armed() {
+                    for at in handle.misfired(missed, now) {
+                        schedule.fire(e.clone(), handle.clone(), at, run.clone());
+                    }
+                }
+            }
+
+            // delay
+            if schedule.delay > 0 {
+                tokio::time::sleep(Duration::from_secs(schedule.delay)).await;
+            }
+            if let Some(since) = deadline(&schedule.since(&tz), handle.now()) {
+                tokio::time::sleep_until(since).await;
+            }
+
+            // run jobs
+            match &schedule.kind {
+                ScheduleKind::Cron(cron) => schedule.on_cron(cron, e, tz, handle, run).await,
+                ScheduleKind::FixedRate(period) => {
+                    schedule.at_fixed_rate(*period, e, tz, handle, run).await
+                }
+                ScheduleKind::FixedDelay(period) => {
+                    schedule.with_fixed_delay(*period, e, tz, handle, run).await
+                }
+            }
+        }));
+    }
+
+    /// Fire on every time matched by the cron expression
+    async fn on_cron<Tz, R>(
+        &self,
+        cron: &Schedule,
+        e: Extensions,
+        tz: Tz,
+        handle: JobHandle,
+        run: R,
+    ) where
+        Tz: TimeZone + Send + Copy + 'static,
+        <Tz as TimeZone>::Offset: Send,
+        R: Fn(Extensions) -> BoxedRun + Send + Clone + 'static,
+    {
+        let mut missed = vec![];
+        // the clock moved on further than the timer while the loop slept
+        let mut jumped = false;
+        let dst = handle.options().dst;
+        for next in cron_after(cron, &handle.now().with_timezone(&tz), dst) {
+            let scheduled = next.with_timezone(&next.offset().fix());
+            // Calculates the instant of the next job run
+            let at = match deadline(&next, handle.now()) {
+                Some(at) => at,
+                // the loop fell behind the clock, the missed ticks are handled once it caught up
+                None if jumped => {
+                    missed.push(scheduled);
+                    continue;
+                }
+                // the loop was held up by a busy runtime, the tick is late but not missed
+                None => {
+                    if handle.is_armed() {
+                        self.fire(e.clone(), handle.clone(), scheduled, run.clone());
+                    }
+                    continue;
+                }
+            };
+            if !missed.is_empty() && handle.is_armed() {
+                for at in handle.misfired(std::mem::take(&mut missed), handle.now()) {
+                    self.fire(e.clone(), handle.clone(), at, run.clone());
+                }
+            }
+            missed.clear();
+
+            // wait until this job runs
+            let (slept, before) = (Instant::now(), handle.now());
+            tokio::time::sleep_until(at).await;
+            // the timer does not count the time the machine was asleep, the clock does
+            let drift = handle.now().signed_duration_since(before) - to_chrono(slept.elapsed());
+            jumped = drift > to_chrono(LATE);
+            if !handle.is_armed() {
+                continue;
+            }
+            if jumped {
+                missed.push(scheduled);
+                continue;
+            }
+
+            self.fire(e.clone(), handle.clone(), scheduled, run.clone());
+        }
+    }
+
+    /// Fire every `period`, a tick is missed when the next one is already due
+    async fn at_fixed_rate<Tz, R>(
+        &self,
+        period: Duration,
+        e: Extensions,
+        tz: Tz,
+        handle: JobHandle,
+        run: R,
+    ) where
+        Tz: TimeZone + Send + Copy + 'static,
+        <Tz as TimeZone>::Offset: Send,
+        R: Fn(Extensions) -> BoxedRun + Send + Clone + 'static,
+    {
+        let mut ticks = tokio::time::interval_at(Instant::now() + period, period);
+        // every late tick is returned, so the misfire policy decides which of them fire
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Burst);
+        let mut missed = vec![];
+        loop {
+            let tick = ticks.tick().await;
+            let late = tick.elapsed();
+            let at = scheduled_at(&tz, handle.now(), late);
+            if late >= period {
+                missed.push(at);
+                continue;
+            }
+            if !missed.is_empty() && handle.is_armed() {
+                for at in handle.misfired(std::mem::take(&mut missed), handle.now()) {
+                    self.fire(e.clone(), handle.clone(), at, run.clone());
+                }
+            }
+            missed.clear();
+            if handle.is_armed() {
+                self.fire(e.clone(), handle.clone(), at, run.clone());
+            }
+        }
+    }
+
+    /// Fire `period` after the end of the previous run
+    async fn with_fixed_delay<Tz, R>(
+        &self,
+        period: Duration,
+        e: Extensions,
+        tz: Tz,
+        handle: JobHandle,
+        run: R,
+    ) where
+        Tz: TimeZone + Send + Copy + 'static,
+        <Tz as TimeZone>::Offset: Send,
+        R: Fn(Extensions) -> BoxedRun + Send + Clone + 'static,
+    {
+        let mut ticks = tokio::time::interval_at(Instant::now() + period, period);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            let tick = ticks.tick().await;
+            if handle.is_armed() {
+                let at = scheduled_at(&tz, handle.now(), tick.elapsed());
+                let _ = self.fire(e.clone(), handle.clone(), at, run.clone()).await;
+            }
+            // count the next period from the end of this run
+            ticks.reset();
+        }
+    }
+
+    /// Start the run of one tick, the runs must not hold up the next tick
+    fn fire<R>(
+        &self,
+        e: Extensions,
+        handle: JobHandle,
+        at: DateTime<FixedOffset>,
+        run: R,
+    ) -> JoinHandle<Option<()>>
+    where
+        R: Fn(Extensions) -> BoxedRun + Send + Clone + 'static,
+    {
+        let repeat = self.clone().repeat(e, handle.clone(), at, run);
+        let admitted = handle.clone();
+        tokio::spawn(handle.until_cancelled(async move {
+            let Some(admission) = admitted.admit().await else {
+                return;
+            };
+            // a queued run may have waited past a pause or the start of the shutdown
+            if admitted.is_armed() {
+                admitted.run_admitted(admission, repeat).await;
+            }
+        }))
+    }
+
+    /// Handle repeat
+    async fn repeat<R>(self, e: Extensions, handle: JobHandle, at: DateTime<FixedOffset>, run: R)
+    where
+        R: Fn(Extensions) -> BoxedRun + Send + Clone + 'static,
+    {
+        // the run is over once every repeat is done, they are aborted if the run is dropped
+        let mut spawned = AbortOnDrop(vec![]);
+        for i in 0..self.repeat {
+            if i > 0 && !handle.is_armed() {
+                break;
+            }
+            let call = call(e.clone(), handle.clone(), at, run.clone());
+            if self.is_async {
+                spawned
+                    .0
+                    .push(tokio::spawn(handle.clone().until_cancelled(call)));
+            } else {
+                call.await;
+            }
+            if self.interval > 0 && i < self.repeat - 1 {
+                tokio::time::sleep(Duration::from_secs(self.interval)).await;
+            }
+        }
+        for task in spawned.0.iter_mut() {
+            let _ = task.await;
+        }
+    }
+}
+
+/// The instant of `at`, to the millisecond, `None` once it passed
+fn deadline<Tz: TimeZone>(at: &DateTime<Tz>, now: DateTime<Utc>) -> Option<Instant> {
+    let left = at.timestamp_millis() - now.timestamp_millis();
+    u64::try_from(left)
+        .ok()
+        .map(|left| Instant::now() + Duration::from_millis(left))
+}
+
+/// The time a tick that is `late` was due, in the offset of `tz`
+fn scheduled_at<Tz: TimeZone>(
+    tz: &Tz,
+    now: DateTime<Utc>,
+    late: Duration,
+) -> DateTime<FixedOffset> {
+    let at = (now - to_chrono(late)).with_timezone(tz);
+    at.with_timezone(&at.offset().fix())
+}
+
+/// Call the handler, retry it while it fails, then pass the last error to the hooks
+async fn call<R>(e: Extensions, handle: JobHandle, at: DateTime<FixedOffset>, run: R)
+where
+    R: Fn(Extensions) -> BoxedRun + Send + Clone + 'static,
+{
+    let retry = handle.options().retry;
+    let timeout = handle.options().timeout;
+    let max_attempts = retry.map_or(1, |retry| retry.max_attempts.max(1));
+    let mut attempt = 1;
+    loop {
+        let started_at = handle.now();
+        let call = run(e.clone());
+        let outcome = handle
+            .clone()
+            .track(at, attempt, async move {
+                match timeout {
+                    // the task of the handler is aborted once `call` is dropped
+                    Some(timeout) => tokio::time::timeout(timeout, call)
+                        .await
+                        .unwrap_or(RunOutcome::TimedOut(timeout)),
+                    None => call.await,
+                }
+            })
+            .await;
+        handle.record(at, attempt, started_at, outcome.clone());
+        if let RunOutcome::Panicked(message) = &outcome {
+            handle.panicked(at, attempt, message);
+        }
+        match (outcome, retry) {
+            (RunOutcome::Success, _) => {
+                handle.succeeded(at);
+                return;
+            }
+            (_, Some(retry)) if attempt < max_attempts && handle.is_armed() => {
+                tokio::time::sleep(retry.backoff.delay(attempt)).await;
+                attempt += 1;
+            }
+            (RunOutcome::Failed(err), _) => {
+                handle.failed(at, attempt, &err);
+                return;
+            }
+            (RunOutcome::TimedOut(timeout), _) => {
+                handle.failed(at, attempt, &JobError::timed_out(timeout));
+                return;
+            }
+            (RunOutcome::Panicked(_), _) => return,
+        }
+    }
+}
+
+/// Wait for the task calling the handler, the panic of the handler is caught here
+/// Call a sync handler on the blocking thread pool, the scheduler counts it until it returns
+pub(crate) fn call_blocking<F>(handle: &JobHandle, f: F) -> BoxedRun
+where
+    F: FnOnce() -> Result<(), JobError> + Send + 'static,
+{
+    let call = handle.enter_blocking();
+    Box::pin(async move {
+        let outcome = join(tokio::task::spawn_blocking(f)).await;
+        drop(call);
+        outcome
+    })
+}
+
+pub(crate) async fn join(task: JoinHandle<Result<(), JobError>>) -> RunOutcome {
+    let mut task = AbortOnDrop(vec![task]);
+    match (&mut task.0[0]).await {
+        Ok(res) => res.into(),
+        Err(err) if err.is_panic() => {
+            let payload = err.into_panic();
+            let message = if let Some(message) = payload.downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                message.clone()
+            } else {
+                "Box<dyn Any>".to_string()
+            };
+            RunOutcome::Panicked(message)
+        }
+        Err(err) => RunOutcome::Failed(JobError::new(err.to_string())),
+    }
+}
+
+struct AbortOnDrop<T>(Vec<JoinHandle<T>>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        for task in self.0.iter() {
+            task.abort();
+        }
+    }
+}
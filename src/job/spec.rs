@@ -0,0 +1,496 @@
+use std::{
+    collections::HashMap, error::Error, fmt, future::Future, path::PathBuf, pin::Pin, sync::Arc,
+    time::Duration,
+};
+
+use chrono::{Datelike, NaiveDateTime, TimeZone, Timelike};
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+
+use crate::{extensions::Extensions, scheduler::BoxedJob, store::StoreError, timezone::JobTz};
+
+use super::{
+    jobschedule::{JobSchedule, JobScheduleBuilder, ScheduleError},
+    runner::{call_blocking, join, BoxedRun},
+    AsyncHandler, ConcurrencyPolicy, DstPolicy, Job, JobError, JobHandle, JobOptions,
+    MisfirePolicy, Retry, SyncHandler,
+};
+
+/// A job defined as data, it is bound to a handler of the `HandlerRegistry` by the `handler` key.
+///
+/// With the `serde` feature it is (de)serializable, see `JobArgs` for a spec read from JSON.
+///
+/// ### Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use tokio_easy_timer::{ConcurrencyPolicy, JobSpec};
+/// let spec = JobSpec {
+///     timeout: Some(Duration::from_secs(30)),
+///     concurrency: ConcurrencyPolicy::Skip,
+///     ..JobSpec::new("digest", "0 0 8 * * *", "send_digest")
+/// };
+/// assert_eq!(spec.repeat, 1);
+/// assert_eq!(spec.interval, Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JobSpec {
+    /// The name of the job, it is also the key of the job in the store
+    pub name: String,
+    /// A cron expression with seconds, such as `0 */5 * * * *`, or a 5 field crontab line, it may also be given as `schedule`.
+    ///
+    /// It is left empty when the job runs `every` period or with a `fixed_delay`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(alias = "schedule", default, skip_serializing_if = "String::is_empty")
+    )]
+    pub cron: String,
+    /// See `JobBuilder::every_duration`, in seconds, it takes the place of `cron`
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            with = "super::secs::option",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub every: Option<Duration>,
+    /// See `JobBuilder::with_fixed_delay`, in seconds, it takes the place of `cron`
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            with = "super::secs::option",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub fixed_delay: Option<Duration>,
+    /// The key the handler was registered with
+    pub handler: String,
+    /// Passed to the handler as `Data<JobArgs>`
+    #[cfg(feature = "serde")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "serde_json::Value::is_null")
+    )]
+    pub args: serde_json::Value,
+    /// See `JobBuilder::tag`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub tags: Vec<String>,
+    /// How many times the handler is called in a row on every tick
+    #[cfg_attr(feature = "serde", serde(default = "one"))]
+    pub repeat: u32,
+    /// The time between the calls repeated on every tick, in whole seconds
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default = "one_sec",
+            with = "super::secs",
+            skip_serializing_if = "is_one_sec"
+        )
+    )]
+    pub interval: Duration,
+    /// See `JobBuilder::since_datetime`, the local time such as `2024-01-01T08:00:00`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub since: Option<NaiveDateTime>,
+    /// See `JobBuilder::after`, in whole seconds
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            with = "super::secs",
+            skip_serializing_if = "Duration::is_zero"
+        )
+    )]
+    pub delay: Duration,
+    /// See `JobBuilder::timeout`, in seconds
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            with = "super::secs::option",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub timeout: Option<Duration>,
+    /// See `JobBuilder::concurrency`
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_default"))]
+    pub concurrency: ConcurrencyPolicy,
+    /// See `JobBuilder::misfire`
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_default"))]
+    pub misfire: MisfirePolicy,
+    /// See `JobBuilder::retry`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub retry: Option<Retry>,
+    /// See `JobBuilder::timezone`, such as `Europe/Berlin` with the `chrono-tz` feature
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub timezone: Option<JobTz>,
+    /// See `JobBuilder::dst`, such as `["run_both", "skip"]`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub dst: Vec<DstPolicy>,
+}
+
+impl JobSpec {
+    /// A job that calls the handler registered as `handler` on every time matched by `cron`
+    pub fn new(
+        name: impl Into<String>,
+        cron: impl Into<String>,
+        handler: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            cron: cron.into(),
+            handler: handler.into(),
+            every: None,
+            fixed_delay: None,
+            #[cfg(feature = "serde")]
+            args: serde_json::Value::Null,
+            tags: vec![],
+            repeat: 1,
+            interval: one_sec(),
+            since: None,
+            delay: Duration::ZERO,
+            timeout: None,
+            concurrency: ConcurrencyPolicy::default(),
+            misfire: MisfirePolicy::default(),
+            retry: None,
+            timezone: None,
+            dst: vec![],
+        }
+    }
+
+    /// The schedule of the job, as the `JobBuilder` would build it
+    fn schedule(&self) -> Result<JobSchedule, SpecError> {
+        let mut builder = JobScheduleBuilder::new();
+        if !self.cron.is_empty() || self.every.is_none() && self.fixed_delay.is_none() {
+            builder.cron(&self.cron);
+        }
+        builder.fixed_rate = self.every;
+        builder.fixed_delay = self.fixed_delay;
+        builder.repeat = self.repeat;
+        builder.interval = self.interval.as_secs();
+        builder.delay = self.delay.as_secs();
+        if let Some(since) = self.since {
+            builder.since = (
+                since.year(),
+                since.month(),
+                since.day(),
+                since.hour(),
+                since.minute(),
+                since.second(),
+            );
+        }
+        builder.try_build().map_err(|err| match err {
+            ScheduleError::InvalidCron { message, .. } => SpecError::InvalidCron {
+                job: self.name.clone(),
+                message,
+            },
+            err => SpecError::InvalidSchedule {
+                job: self.name.clone(),
+                error: err,
+            },
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+fn one() -> u32 {
+    1
+}
+
+fn one_sec() -> Duration {
+    Duration::from_secs(1)
+}
+
+#[cfg(feature = "serde")]
+fn is_one_sec(d: &Duration) -> bool {
+    *d == one_sec()
+}
+
+#[cfg(feature = "serde")]
+fn is_default<T: Default + PartialEq>(t: &T) -> bool {
+    *t == T::default()
+}
+
+/// The arguments of a job defined by a `JobSpec`, the handler takes them as `Data<JobArgs>`
+///
+/// ### Example
+///
+/// ```rust
+/// # use std::time::Duration;
+/// # use tokio_easy_timer::{ConcurrencyPolicy, Data, HandlerRegistry, JobArgs, JobSpec};
+/// #[derive(serde::Deserialize)]
+/// struct Digest {
+///     to: String,
+/// }
+///
+/// let spec: JobSpec = serde_json::from_str(r#"{
+///     "name": "digest",
+///     "cron": "0 0 8 * * *",
+///     "handler": "send_digest",
+///     "args": { "to": "team@example.com" },
+///     "timeout": 30,
+///     "concurrency": "skip",
+///     "retry": { "max_attempts": 3, "backoff": { "fixed": 60 } }
+/// }"#).unwrap();
+/// assert_eq!(spec.timeout, Some(Duration::from_secs(30)));
+/// assert_eq!(spec.concurrency, ConcurrencyPolicy::Skip);
+///
+/// let mut registry = HandlerRegistry::new();
+/// registry.register("send_digest", |args: Data<JobArgs>| async move {
+///     let digest: Digest = args.parse()?;
+///     println!("send the digest to {}", digest.to);
+///     Ok::<(), tokio_easy_timer::JobError>(())
+/// });
+/// assert!(registry.build::<chrono::Utc>(&spec).is_ok());
+/// ```
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobArgs(serde_json::Value);
+
+#[cfg(feature = "serde")]
+impl JobArgs {
+    /// Returns the arguments as they were given
+    pub fn value(&self) -> &serde_json::Value {
+        &self.0
+    }
+
+    /// Deserialize the arguments into `T`
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T, JobError> {
+        T::deserialize(&self.0).map_err(|err| JobError::new(format!("invalid job args: {}", err)))
+    }
+}
+
+type AsyncFn = Arc<
+    dyn Fn(&Extensions) -> Pin<Box<dyn Future<Output = Result<(), JobError>> + Send>> + Send + Sync,
+>;
+
+type SyncFn = Arc<dyn Fn(&Extensions) -> Result<(), JobError> + Send + Sync>;
+
+#[derive(Clone)]
+enum BoxedHandler {
+    Async(AsyncFn),
+    Sync(SyncFn),
+}
+
+impl BoxedHandler {
+    /// Call the handler like `AsyncJob` and `SyncJob` do
+    fn run(&self, handle: &JobHandle, e: Extensions) -> BoxedRun {
+        match self {
+            BoxedHandler::Async(f) => Box::pin(join(tokio::spawn(f(&e)))),
+            BoxedHandler::Sync(f) => {
+                let f = f.clone();
+                call_blocking(handle, move || f(&e))
+            }
+        }
+    }
+}
+
+/// Handlers bound to a key, jobs defined by a `JobSpec` find their handler here
+///
+/// ### Example
+///
+/// ```rust
+/// # use tokio_easy_timer::HandlerRegistry;
+/// let mut registry = HandlerRegistry::new();
+/// registry
+///     .register("send_digest", || async { println!("send the digest") })
+///     .register_sync("vacuum", || println!("vacuum"));
+/// assert!(registry.contains("vacuum"));
+/// ```
+#[derive(Default, Clone)]
+pub struct HandlerRegistry {
+    handlers: HashMap<String, BoxedHandler>,
+}
+
+impl HandlerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind an async handler to `key`, an older handler with the same key is replaced
+    pub fn register<Args, F>(&mut self, key: impl Into<String>, f: F) -> &mut Self
+    where
+        F: AsyncHandler<Args> + Send + Sync + Copy + 'static,
+    {
+        self.handlers.insert(
+            key.into(),
+            BoxedHandler::Async(Arc::new(move |e| f.call(e))),
+        );
+        self
+    }
+
+    /// Bind a sync handler to `key`, it runs on a blocking thread like the handler of a `SyncJob`
+    pub fn register_sync<Args, F>(&mut self, key: impl Into<String>, f: F) -> &mut Self
+    where
+        F: SyncHandler<Args> + Send + Sync + 'static,
+    {
+        self.handlers
+            .insert(key.into(), BoxedHandler::Sync(Arc::new(move |e| f.call(e))));
+        self
+    }
+
+    /// Returns the keys of every handler, in no particular order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.handlers.keys().map(String::as_str)
+    }
+
+    /// Returns `true` if a handler is bound to `key`
+    pub fn contains(&self, key: &str) -> bool {
+        self.handlers.contains_key(key)
+    }
+
+    /// Create the job described by `spec`, it is added to the scheduler like any other job
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// # use tokio_easy_timer::{HandlerRegistry, JobSpec};
+    /// let mut registry = HandlerRegistry::new();
+    /// registry.register("ping", || async { println!("pong") });
+    ///
+    /// let mut s = Scheduler::new();
+    /// let spec = JobSpec::new("ping", "*/10 * * * * *", "ping");
+    /// let handle = s.add_job(registry.build(&spec).unwrap());
+    /// assert_eq!(handle.name(), Some("ping"));
+    /// assert!(registry.build::<chrono::Local>(&JobSpec::new("pong", "* * * * * *", "pong")).is_err());
+    /// ```
+    pub fn build<Tz>(&self, spec: &JobSpec) -> Result<BoxedJob<Tz>, SpecError>
+    where
+        Tz: TimeZone + Send + Copy + 'static,
+        <Tz as TimeZone>::Offset: Send,
+    {
+        let handler = self
+            .handlers
+            .get(&spec.handler)
+            .ok_or_else(|| SpecError::UnknownHandler {
+                job: spec.name.clone(),
+                handler: spec.handler.clone(),
+            })?
+            .clone();
+        let mut options = JobOptions {
+            name: Some(spec.name.clone()),
+            tags: spec.tags.clone(),
+            concurrency: spec.concurrency,
+            retry: spec.retry,
+            timeout: spec.timeout,
+            misfire: spec.misfire,
+            timezone: spec.timezone,
+            ..JobOptions::default()
+        };
+        for policy in &spec.dst {
+            options.dst.set(*policy);
+        }
+        Ok(Box::new(SpecJob {
+            handler,
+            #[cfg(feature = "serde")]
+            args: JobArgs(spec.args.clone()),
+            jobschedules: vec![spec.schedule()?],
+            options,
+        }))
+    }
+}
+
+#[derive(Clone)]
+struct SpecJob {
+    handler: BoxedHandler,
+    #[cfg(feature = "serde")]
+    args: JobArgs,
+    jobschedules: Vec<JobSchedule>,
+    options: JobOptions,
+}
+
+impl<Tz> Job<Tz> for SpecJob
+where
+    Tz: TimeZone + Send + Copy + 'static,
+    <Tz as TimeZone>::Offset: Send,
+{
+    fn box_clone(&self) -> Box<dyn Job<Tz> + Send + 'static> {
+        Box::new(self.clone())
+    }
+
+    fn options(&self) -> &JobOptions {
+        &self.options
+    }
+
+    fn schedules(&self) -> &[JobSchedule] {
+        &self.jobschedules
+    }
+
+    fn start_schedule(&self, e: Extensions, tz: Tz, handle: JobHandle) {
+        let e = e.child();
+        #[cfg(feature = "serde")]
+        e.insert(self.args.clone());
+        for schedule in self.jobschedules.iter() {
+            let handler = self.handler.clone();
+            let job = handle.clone();
+            schedule.start(e.clone(), tz, handle.clone(), move |e| handler.run(&job, e));
+        }
+    }
+}
+
+/// Why the jobs defined as data could not be loaded
+#[derive(Debug)]
+pub enum SpecError {
+    /// No handler is bound to the key of the job
+    UnknownHandler { job: String, handler: String },
+    /// The cron expression of the job can not be parsed
+    InvalidCron { job: String, message: String },
+    /// Another value of the schedule of the job is not valid, or it has both a `cron` and an `every` period
+    InvalidSchedule { job: String, error: ScheduleError },
+    /// The config file can not be read or parsed
+    Config { path: PathBuf, message: String },
+    /// The store could not be read
+    Store(StoreError),
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::UnknownHandler { job, handler } => {
+                write!(f, "job {}: no handler is registered as {}", job, handler)
+            }
+            SpecError::InvalidCron { job, message } => {
+                write!(f, "job {}: invalid cron expression: {}", job, message)
+            }
+            SpecError::InvalidSchedule { job, error } => write!(f, "job {}: {}", job, error),
+            SpecError::Config { path, message } => {
+                write!(f, "config {}: {}", path.display(), message)
+            }
+            SpecError::Store(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for SpecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SpecError::Store(err) => Some(err),
+            SpecError::InvalidSchedule { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<StoreError> for SpecError {
+    fn from(err: StoreError) -> Self {
+        SpecError::Store(err)
+    }
+}
@@ -1,18 +1,21 @@
 use crate::extensions::{Data, Extensions};
 
+use super::{HandlerResult, JobError};
+
 pub trait SyncHandler<Args> {
-    fn call(&self, e: &Extensions);
+    fn call(&self, e: &Extensions) -> Result<(), JobError>;
 }
 
 macro_rules! impl_handler {
     ($( $P:ident ),*) => {
-        impl<F, $($P,)*> SyncHandler<($(Data<$P>,)*)> for F
+        impl<F, O, $($P,)*> SyncHandler<($(Data<$P>,)*)> for F
         where
             $( $P: Clone + 'static + Send + Sync, )*
-            F: Fn($(Data<$P>,)*),
+            O: HandlerResult,
+            F: Fn($(Data<$P>,)*) -> O,
         {
-            fn call(&self, _e: &Extensions) {
-                self($(_e.get_data::<$P>(),)*);
+            fn call(&self, _e: &Extensions) -> Result<(), JobError> {
+                self($(_e.get_data::<$P>(),)*).into_result()
             }
         }
     };
@@ -1,26 +1,28 @@
 use std::marker::PhantomData;
 
-use std::time::Duration;
-
 use chrono::TimeZone;
 
 use crate::{extensions::Extensions, interval::Interval, scheduler::BoxedJob};
 
 use super::{
-    jobschedule::{JobSchedule, JobScheduleBuilder},
-    Job, JobBuilder, SyncHandler,
+    jobschedule::{JobSchedule, JobScheduleBuilder, ScheduleError},
+    runner::call_blocking,
+    Job, JobBuilder, JobHandle, JobOptions, SyncHandler,
 };
 
 #[derive(Clone)]
 pub struct SyncJob<Args, F> {
     pub f: F,
     pub jobschedules: Vec<JobSchedule>,
+    pub options: JobOptions,
     pub _phantom: PhantomData<Args>,
 }
 
 pub struct SyncJobBuilder<Args> {
     jobschedules: Vec<JobSchedule>,
     builder: JobScheduleBuilder,
+    options: JobOptions,
+    error: Option<ScheduleError>,
     _phantom: PhantomData<Args>,
 }
 
@@ -31,74 +33,25 @@ where
     Tz: TimeZone + Send + Copy + 'static,
     <Tz as TimeZone>::Offset: Send,
 {
-    fn box_clone(&self) -> Box<(dyn Job<Tz> + Send + 'static)> {
+    fn box_clone(&self) -> Box<dyn Job<Tz> + Send + 'static> {
         Box::new((*self).clone())
     }
 
-    fn start_schedule(&self, e: Extensions, tz: Tz) {
+    fn options(&self) -> &JobOptions {
+        &self.options
+    }
+
+    fn schedules(&self) -> &[JobSchedule] {
+        &self.jobschedules
+    }
+
+    fn start_schedule(&self, e: Extensions, tz: Tz, handle: JobHandle) {
+        let f = self.f;
         for schedule in self.jobschedules.iter() {
             // spawn a task for every corn schedule
-            let f = self.f.clone();
-            let e = e.clone();
-            let schedule = schedule.clone();
-            tokio::spawn(async move {
-                // delay
-                if schedule.delay > 0 {
-                    tokio::time::sleep(Duration::from_secs(schedule.delay)).await;
-                }
-                let now = chrono::Local::now().with_timezone(&tz);
-                let since = schedule.since;
-                let wait_to = tz
-                    .ymd(since.0, since.1, since.2)
-                    .and_hms(since.3, since.4, since.5);
-                let d = wait_to.timestamp() - now.timestamp();
-                if d > 0 {
-                    tokio::time::sleep(Duration::from_secs(d as u64)).await;
-                }
-
-                // run jobs
-                for next in schedule.schedule.upcoming(tz) {
-                    let e = e.clone();
-
-                    // Calculates the time left until the next job run
-                    let now = chrono::Local::now().with_timezone(&tz);
-                    let d = next.timestamp() - now.timestamp();
-                    if d < 0 {
-                        continue;
-                    }
-
-                    // prepare a task for the job
-                    tokio::spawn(async move {
-                        // Wait until the next job runs
-                        tokio::time::sleep(Duration::from_secs(d as u64)).await;
-
-                        // Handle repeat
-                        if schedule.is_async {
-                            for i in 0..schedule.repeat {
-                                let e = e.clone();
-                                tokio::task::spawn_blocking(move || {
-                                    f.call(&e);
-                                });
-                                if i < schedule.repeat - 1 {
-                                    tokio::time::sleep(Duration::from_secs(schedule.interval))
-                                        .await;
-                                }
-                            }
-                        } else {
-                            tokio::task::spawn_blocking(move || {
-                                for i in 0..schedule.repeat {
-                                    f.call(&e);
-                                    if schedule.interval > 0 && i < schedule.repeat - 1 {
-                                        std::thread::sleep(Duration::from_secs(schedule.interval));
-                                    }
-                                }
-                            });
-                        }
-                    });
-
-                    // wait until this task run
-                    tokio::time::sleep(Duration::from_secs(d as u64)).await;
-                }
+            let job = handle.clone();
+            schedule.start(e.clone(), tz, handle.clone(), move |e| {
+                call_blocking(&job, move || f.call(&e))
             });
         }
     }
@@ -108,19 +61,33 @@ impl<Args> SyncJobBuilder<Args>
 where
     Args: Clone + 'static + Send,
 {
-    /// Constructs a new sync job
+    /// Constructs a new sync job, panics if a schedule is not valid
     pub fn run<Tz, F>(&mut self, f: F) -> BoxedJob<Tz>
+    where
+        F: SyncHandler<Args> + Send + 'static + Copy,
+        Tz: TimeZone + Clone + Send + Sync + Copy + 'static,
+        <Tz as TimeZone>::Offset: Send + Sync,
+    {
+        self.try_run(f).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Constructs a new sync job, or returns the first invalid value given to the builder
+    pub fn try_run<Tz, F>(&mut self, f: F) -> Result<BoxedJob<Tz>, ScheduleError>
     where
         F: SyncHandler<Args> + Send + 'static + Copy,
         Tz: TimeZone + Clone + Send + Sync + Copy + 'static,
         <Tz as TimeZone>::Offset: Send + Sync,
     {
         self.and();
-        Box::new(SyncJob {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        Ok(Box::new(SyncJob {
             f,
             jobschedules: self.jobschedules.clone(),
+            options: self.options.clone(),
             _phantom: PhantomData,
-        })
+        }))
     }
 }
 
@@ -130,11 +97,18 @@ impl<Args> JobBuilder<Args> for SyncJobBuilder<Args> {
             _phantom: PhantomData,
             jobschedules: vec![],
             builder: JobScheduleBuilder::new(),
+            options: JobOptions::default(),
+            error: None,
         }
     }
 
     fn and(&mut self) -> &mut Self {
-        self.jobschedules.push(self.builder.build());
+        match self.builder.try_build() {
+            Ok(schedule) => self.jobschedules.push(schedule),
+            Err(err) => {
+                self.error.get_or_insert(err);
+            }
+        }
         self.builder = JobScheduleBuilder::new();
         self
     }
@@ -143,20 +117,22 @@ impl<Args> JobBuilder<Args> for SyncJobBuilder<Args> {
         &mut self.builder
     }
 
+    fn get_mut_options(&mut self) -> &mut JobOptions {
+        &mut self.options
+    }
+
     fn get_mut_since(&mut self) -> &mut (i32, u32, u32, u32, u32, u32) {
         &mut self.builder.since
     }
 
     fn repeat_seq(&mut self, n: u32, interval: Interval) -> &mut Self {
-        self.builder.repeat = n;
-        self.builder.interval = interval.to_sec();
+        self.builder.repeat(n, interval);
         self
     }
 
     fn repeat_async(&mut self, n: u32, interval: Interval) -> &mut Self {
         self.builder.is_async = true;
-        self.builder.repeat = n;
-        self.builder.interval = interval.to_sec();
+        self.builder.repeat(n, interval);
         self
     }
 }
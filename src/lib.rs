@@ -1,15 +1,39 @@
+mod clock;
 mod extensions;
 pub mod interval;
 mod job;
 mod scheduler;
+mod store;
+#[cfg(feature = "testing")]
+mod testing;
+mod timezone;
 
+pub use clock::{Clock, MockClock, SystemClock};
 pub use extensions::Data;
-pub use job::{AsyncJobBuilder as AsyncJob, JobBuilder, SyncJobBuilder as SyncJob};
-pub use scheduler::Scheduler;
+#[cfg(feature = "serde")]
+pub use job::JobArgs;
+pub use job::{
+    AsyncJobBuilder as AsyncJob, Backoff, ConcurrencyPolicy, DstPolicy, JobBuilder, JobError,
+    JobHandle, JobId, JobOptions, JobScheduleBuilder, JobStatus, Misfire, MisfirePolicy, Retry,
+    RunInfo, RunOutcome, RunRecord, ScheduleError, SyncJobBuilder as SyncJob,
+};
+pub use job::{HandlerRegistry, JobSpec, SpecError};
+pub use scheduler::{Scheduler, SchedulerHandle, ShutdownReport};
+#[cfg(feature = "serde")]
+pub use store::JsonFileStore;
+#[cfg(feature = "sqlite")]
+pub use store::SqliteStore;
+pub use store::{JobStore, MemoryStore, StoreError};
+#[cfg(feature = "testing")]
+pub use testing::RecordingScheduler;
+pub use timezone::{JobTz, JobTzOffset};
 
 pub mod prelude {
     pub use crate::interval::{Interval, TimeUnits};
     pub use crate::Data;
-    pub use crate::Scheduler;
-    pub use crate::{AsyncJob, JobBuilder, SyncJob};
+    pub use crate::{
+        AsyncJob, Backoff, ConcurrencyPolicy, JobBuilder, JobHandle, JobStatus, MisfirePolicy,
+        SyncJob,
+    };
+    pub use crate::{Scheduler, SchedulerHandle};
 }
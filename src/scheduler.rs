@@ -1,16 +1,101 @@
-use chrono::TimeZone;
+use std::{future::Future, ops::Deref, sync::Arc, time::Duration};
 
+use chrono::{DateTime, Offset, TimeZone};
+use parking_lot::Mutex;
+
+use crate::clock::Clock;
 use crate::extensions::Extensions;
-use crate::job::Job;
+#[cfg(feature = "serde")]
+use crate::job::read_config;
+use crate::job::{
+    upcoming, Hooks, Job, JobError, JobHandle, JobId, JobStatus, Misfire, RunInfo, Timeline,
+};
+use crate::job::{HandlerRegistry, JobSpec, SpecError};
+use crate::store::JobStore;
+#[cfg(feature = "chrono-tz")]
+use crate::ScheduleError;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+mod watch;
 
 pub type BoxedJob<Tz> = Box<dyn Job<Tz> + Send + 'static>;
+
+/// What happened to the runs that were still executing when the scheduler shut down
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownReport {
+    /// The runs that did not finish before the deadline.
+    ///
+    /// Async handlers are aborted, sync handlers keep running on the blocking thread pool but are no longer waited for.
+    pub aborted: Vec<RunInfo>,
+}
+
+impl ShutdownReport {
+    /// Returns `true` if every run finished before the deadline
+    pub fn is_clean(&self) -> bool {
+        self.aborted.is_empty()
+    }
+}
+
 pub struct Scheduler<Tz = chrono::Local>
 where
     Tz: chrono::TimeZone,
 {
-    jobs: Vec<BoxedJob<Tz>>,
+    handle: SchedulerHandle<Tz>,
+}
+
+/// A cloneable handle to a scheduler, it can add, remove and replace jobs while the scheduler is running.
+///
+/// The scheduler derefs to its handle, so the methods to find and remove jobs work on both.
+///
+/// ### Example
+///
+/// ```rust
+/// # use tokio_easy_timer::prelude::*;
+/// # #[tokio::main]
+/// # async fn main() {
+/// let scheduler = Scheduler::new();
+/// scheduler.run().await;
+///
+/// let handle = scheduler.handle();
+/// let job = handle.add(AsyncJob::new().every(10.seconds()).run(|| async {}));
+/// handle.replace(job.id(), AsyncJob::new().every(20.seconds()).run(|| async {}));
+/// assert!(handle.remove(job.id()));
+/// # }
+/// ```
+pub struct SchedulerHandle<Tz = chrono::Local>
+where
+    Tz: chrono::TimeZone,
+{
+    shared: Arc<Shared<Tz>>,
+}
+
+impl<Tz: chrono::TimeZone> Clone for SchedulerHandle<Tz> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+enum State {
+    Idle,
+    Running,
+    Stopped,
+}
+
+struct Registry<Tz: chrono::TimeZone> {
+    jobs: Vec<(BoxedJob<Tz>, JobHandle)>,
+    state: State,
+}
+
+struct Shared<Tz: chrono::TimeZone> {
+    registry: Mutex<Registry<Tz>>,
     tz: Tz,
     extensions: Extensions,
+    hooks: Arc<Hooks>,
 }
 
 impl Scheduler {
@@ -21,31 +106,88 @@ impl Scheduler {
     /// ### Example
     ///
     /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
     /// let s = Scheduler::new();
     /// ```
     pub fn new() -> Scheduler {
-        Scheduler {
-            extensions: Extensions::default(),
-            jobs: vec![],
-            tz: chrono::Local,
-        }
+        Scheduler::with_tz(chrono::Local)
     }
 
     /// if you want a specified timezone instead of the mathine timezone `chrono::Local`, use this
     pub fn with_tz<Tz: chrono::TimeZone>(tz: Tz) -> Scheduler<Tz> {
         Scheduler {
-            extensions: Extensions::default(),
-            jobs: vec![],
-            tz,
+            handle: SchedulerHandle {
+                shared: Arc::new(Shared {
+                    registry: Mutex::new(Registry {
+                        jobs: vec![],
+                        state: State::Idle,
+                    }),
+                    tz,
+                    extensions: Extensions::default(),
+                    hooks: Arc::default(),
+                }),
+            },
         }
     }
 
+    /// A scheduler in the timezone of the IANA database named `name`, such as `America/New_York`.
+    ///
+    /// Unlike a `FixedOffset`, the timezone follows its daylight saving rules: a job at 09:00 keeps running at 09:00
+    /// on the wall clock. The local times that are skipped or repeated when the clocks change follow the `DstPolicy` of the job.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use chrono::{TimeZone, Utc};
+    /// # use tokio_easy_timer::prelude::*;
+    /// # use tokio_easy_timer::MockClock;
+    /// let mut s = Scheduler::with_tz_name("Europe/Berlin").unwrap();
+    /// let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 3, 30, 6, 0, 0).unwrap());
+    /// s.set_clock(clock.clone());
+    /// let nine = s.add_job(AsyncJob::new().at_time(9, 0, 0).run(|| async {}));
+    /// let night = s.add_job(AsyncJob::new().at_time(2, 30, 0).run(|| async {}));
+    /// let hourly = s.add_job(AsyncJob::new().cron("30 * * * *").run(|| async {}));
+    ///
+    /// // 09:00 in winter, then 09:00 in summer
+    /// assert_eq!(
+    ///     nine.upcoming(2),
+    ///     [Utc.with_ymd_and_hms(2024, 3, 30, 8, 0, 0).unwrap(), Utc.with_ymd_and_hms(2024, 3, 31, 7, 0, 0).unwrap()]
+    /// );
+    /// // the clocks move from 02:00 to 03:00 on March 31, 02:30 is shifted to 03:30 summer time
+    /// let spring = |h| Utc.with_ymd_and_hms(2024, 3, 31, h, 30, 0).unwrap();
+    /// assert_eq!(night.upcoming(2), [spring(1), Utc.with_ymd_and_hms(2024, 4, 1, 0, 30, 0).unwrap()]);
+    /// clock.set(Utc.with_ymd_and_hms(2024, 3, 31, 0, 0, 0).unwrap());
+    /// // 01:30 winter time, 03:30 summer time once, 04:30 summer time
+    /// assert_eq!(hourly.upcoming(3), [spring(0), spring(1), spring(2)]);
+    ///
+    /// // the clocks move from 03:00 back to 02:00 on October 27, 02:30 only runs in summer time
+    /// clock.set(Utc.with_ymd_and_hms(2024, 10, 26, 12, 0, 0).unwrap());
+    /// let autumn = |d, h| Utc.with_ymd_and_hms(2024, 10, d, h, 30, 0).unwrap();
+    /// assert_eq!(night.upcoming(2), [autumn(27, 0), autumn(28, 1)]);
+    /// clock.set(Utc.with_ymd_and_hms(2024, 10, 26, 23, 0, 0).unwrap());
+    /// // 01:30 and 02:30 summer time, then 03:30 winter time
+    /// assert_eq!(hourly.upcoming(3), [autumn(26, 23), autumn(27, 0), autumn(27, 2)]);
+    /// assert!(Scheduler::with_tz_name("Europe/Nowhere").is_err());
+    /// ```
+    #[cfg(feature = "chrono-tz")]
+    pub fn with_tz_name(name: &str) -> Result<Scheduler<chrono_tz::Tz>, ScheduleError> {
+        name.parse()
+            .map(Scheduler::with_tz)
+            .map_err(|_| ScheduleError::UnknownTimezone(name.to_string()))
+    }
+
     // pub fn add(mut self, job: BoxedJob) -> Self {
     //     self.jobs.push(job);
     //     self
     // }
 }
 
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<Tz> Scheduler<Tz>
 where
     Tz: TimeZone + Clone + Sync + Send + Copy + 'static,
@@ -56,15 +198,168 @@ where
     where
         T: 'static + Send + Sync,
     {
-        self.extensions.insert(ext);
+        self.shared.extensions.insert(ext);
+    }
+
+    /// Called when the handler of any job returns an error, after the hook set on the job
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::new();
+    /// s.on_error(|run, err| eprintln!("job {} failed: {}", run.job, err));
+    /// s.add(SyncJob::new().every(1.minutes()).run(|| "1x".parse::<u32>().map(|_| ())));
+    /// ```
+    pub fn on_error<F>(&self, f: F)
+    where
+        F: Fn(&RunInfo, &JobError) + Send + Sync + 'static,
+    {
+        *self.shared.hooks.on_error.write() = Some(Arc::new(f));
+    }
+
+    /// Called when the handler of any job panics, with the message of the panic, after the hook set on the job
+    ///
+    /// The panic is caught, the job keeps running on its schedule, see `JobHandle::panic_count`.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// let s = Scheduler::new();
+    /// s.on_panic(|run, msg| eprintln!("job {} panicked: {}", run.job, msg));
+    /// ```
+    pub fn on_panic<F>(&self, f: F)
+    where
+        F: Fn(&RunInfo, &str) + Send + Sync + 'static,
+    {
+        *self.shared.hooks.on_panic.write() = Some(Arc::new(f));
+    }
+
+    /// Called for every tick of any job that went by while the job could not run, after the hook set on the job
+    pub fn on_misfire<F>(&self, f: F)
+    where
+        F: Fn(&RunInfo, &Misfire) + Send + Sync + 'static,
+    {
+        *self.shared.hooks.on_misfire.write() = Some(Arc::new(f));
+    }
+
+    /// Called with every finished call of a handler, the `RecordingScheduler` keeps them
+    #[cfg(feature = "testing")]
+    pub(crate) fn on_run<F>(&self, f: F)
+    where
+        F: Fn(&crate::RunRecord) + Send + Sync + 'static,
+    {
+        *self.shared.hooks.on_run.write() = Some(Arc::new(f));
+    }
+
+    /// Wait until no sync handler or store access of a job is left on the blocking thread pool
+    #[cfg(feature = "testing")]
+    pub(crate) async fn blocking_idle(&self) {
+        self.shared.hooks.blocking.idle().await
+    }
+
+    /// Remember the last run of every named job in `store`, the runs missed while the process was down are then handled on startup by the `MisfirePolicy` of the job.
+    ///
+    /// The store must be set before the scheduler starts.
+    pub fn set_store<S: JobStore + 'static>(&self, store: S) {
+        *self.shared.hooks.store.write() = Some(Arc::new(store));
+    }
+
+    /// Read the time from `clock` instead of the system clock, see `MockClock` to test schedules
+    pub fn set_clock<C: Clock + 'static>(&self, clock: C) {
+        *self.shared.hooks.clock.write() = Arc::new(clock);
     }
 
     /// add a new task to the scheduler, you must privide something that implements `Job` trait.
     pub fn add(&mut self, job: BoxedJob<Tz>) -> &mut Scheduler<Tz> {
-        self.jobs.push(job);
+        self.add_job(job);
         self
     }
 
+    /// add a new task to the scheduler, like `add`, but returns a handle to cancel, pause or resume the job later.
+    ///
+    /// A job added after `shutdown` is not kept, its handle is already cancelled.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::new();
+    /// let handle = s.add_job(AsyncJob::new().every(10.seconds()).run(|| async {}));
+    /// handle.pause();
+    /// assert!(!handle.is_running());
+    /// handle.resume();
+    /// assert!(handle.is_running());
+    /// handle.cancel();
+    /// assert_eq!(handle.status(), JobStatus::Cancelled);
+    /// ```
+    pub fn add_job(&mut self, job: BoxedJob<Tz>) -> JobHandle {
+        self.shared.add(job)
+    }
+
+    /// Add every job kept in `store`, each one is bound to its handler in `registry`.
+    ///
+    /// Nothing is added unless every job can be loaded.
+    pub fn load_jobs(
+        &mut self,
+        store: &dyn JobStore,
+        registry: &HandlerRegistry,
+    ) -> Result<Vec<JobHandle>, SpecError> {
+        self.add_specs(&store.jobs()?, registry)
+    }
+
+    /// Add every job defined in the config file at `path`, each one is bound to its handler in `registry`.
+    ///
+    /// The format is picked by the extension of the file: `toml`, `yaml`, `yml` or `json`.
+    /// Each job is a `JobSpec`, nothing is added unless every job can be loaded.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// # use tokio_easy_timer::HandlerRegistry;
+    /// let path = std::env::temp_dir().join("tokio-easy-timer-jobs.toml");
+    /// std::fs::write(
+    ///     &path,
+    ///     r#"
+    /// [[job]]
+    /// name = "cleanup"
+    /// cron = "0 */15 * * * *"
+    /// handler = "cleanup"
+    /// timeout = 60
+    /// concurrency = "skip"
+    /// "#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut registry = HandlerRegistry::new();
+    /// registry.register("cleanup", || async { println!("cleanup") });
+    /// let mut s = Scheduler::new();
+    /// let jobs = s.load_config(&path, &registry).unwrap();
+    /// assert_eq!(jobs[0].name(), Some("cleanup"));
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn load_config(
+        &mut self,
+        path: impl AsRef<Path>,
+        registry: &HandlerRegistry,
+    ) -> Result<Vec<JobHandle>, SpecError> {
+        self.add_specs(&read_config(path.as_ref())?, registry)
+    }
+
+    fn add_specs(
+        &mut self,
+        specs: &[JobSpec],
+        registry: &HandlerRegistry,
+    ) -> Result<Vec<JobHandle>, SpecError> {
+        let jobs = specs
+            .iter()
+            .map(|spec| registry.build(spec))
+            .collect::<Result<Vec<BoxedJob<Tz>>, _>>()?;
+        Ok(jobs.into_iter().map(|job| self.add_job(job)).collect())
+    }
+
     // pub fn add<Args, F>(&mut self, job: AsyncJob<Args, F>) -> &mut Scheduler<Tz>
     // where
     //     Args: Clone + 'static + Send + Sync,
@@ -75,16 +370,17 @@ where
     //     self
     // }
 
+    /// Returns a cloneable handle, use it to change the jobs after the scheduler started.
+    pub fn handle(&self) -> SchedulerHandle<Tz> {
+        self.handle.clone()
+    }
+
     async fn start_spawn(&self) -> &Self {
-        for job in self.jobs.iter() {
-            let e = self.extensions.clone();
-            let tz = self.tz.clone();
-            {
-                let job = job.box_clone();
-                tokio::spawn(async move {
-                    let job = job;
-                    job.start_schedule(e, tz);
-                });
+        let mut registry = self.shared.registry.lock();
+        if registry.state == State::Idle {
+            registry.state = State::Running;
+            for (job, handle) in registry.jobs.iter() {
+                self.shared.spawn(job, handle);
             }
         }
         self
@@ -100,4 +396,295 @@ where
         self.start_spawn().await;
         std::future::pending::<()>().await;
     }
+
+    /// Start the timer, block the current thread until `signal` completes, then shut down gracefully.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,no_run
+    /// # use std::time::Duration;
+    /// # use tokio_easy_timer::prelude::*;
+    /// # async fn f(scheduler: Scheduler) {
+    /// let report = scheduler
+    ///     .run_until(tokio::signal::ctrl_c(), Duration::from_secs(30))
+    ///     .await;
+    /// for run in report.aborted {
+    ///     eprintln!("job {} scheduled at {} was aborted", run.job, run.scheduled_at);
+    /// }
+    /// # }
+    /// ```
+    pub async fn run_until<F: Future>(&self, signal: F, timeout: Duration) -> ShutdownReport {
+        self.start_spawn().await;
+        signal.await;
+        self.shutdown(timeout).await
+    }
+
+    /// Stop arming new runs, then wait up to `timeout` for the handlers that are already running.
+    ///
+    /// Every job is cancelled afterwards, the report lists the runs that did not finish in time.
+    pub async fn shutdown(&self, timeout: Duration) -> ShutdownReport {
+        let handles: Vec<JobHandle> = {
+            let mut registry = self.shared.registry.lock();
+            registry.state = State::Stopped;
+            registry
+                .jobs
+                .iter()
+                .map(|(_, handle)| handle.clone())
+                .collect()
+        };
+        for handle in handles.iter() {
+            handle.drain();
+        }
+        let deadline = tokio::time::Instant::now() + timeout;
+        for handle in handles.iter() {
+            let _ = tokio::time::timeout_at(deadline, handle.idle()).await;
+        }
+        let mut report = ShutdownReport::default();
+        for handle in handles.iter() {
+            report.aborted.extend(handle.in_flight());
+            handle.cancel();
+        }
+        report
+    }
+}
+
+impl<Tz: chrono::TimeZone> Deref for Scheduler<Tz> {
+    type Target = SchedulerHandle<Tz>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handle
+    }
+}
+
+impl<Tz> SchedulerHandle<Tz>
+where
+    Tz: TimeZone + Clone + Sync + Send + Copy + 'static,
+    <Tz as TimeZone>::Offset: Send + Sync,
+{
+    /// add a new job, it starts right away if the scheduler is running.
+    ///
+    /// A job added after `shutdown` is not kept, its handle is already cancelled.
+    pub fn add(&self, job: BoxedJob<Tz>) -> JobHandle {
+        self.shared.add(job)
+    }
+
+    /// Returns the handles of every job in the scheduler, in the order they were added
+    pub fn jobs(&self) -> Vec<JobHandle> {
+        self.shared.jobs()
+    }
+
+    /// Returns the handle of the job with the given id
+    pub fn get(&self, id: JobId) -> Option<JobHandle> {
+        self.shared.get(id)
+    }
+
+    /// Returns the handles of the jobs with the given tag
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::new();
+    /// s.add(
+    ///     AsyncJob::new()
+    ///         .name("nightly-report")
+    ///         .tag("billing")
+    ///         .at_time(3, 0, 0)
+    ///         .run(|| async {}),
+    /// );
+    /// let jobs = s.find_by_tag("billing");
+    /// assert_eq!(jobs[0].name(), Some("nightly-report"));
+    /// assert_eq!(s.cancel_by_tag("billing"), 1);
+    /// assert_eq!(jobs[0].status(), JobStatus::Cancelled);
+    /// assert!(s.find_by_tag("billing").is_empty());
+    /// assert_eq!(s.cancel_by_tag("billing"), 0);
+    /// ```
+    pub fn find_by_tag(&self, tag: &str) -> Vec<JobHandle> {
+        self.shared.find_by_tag(tag)
+    }
+
+    /// Cancel every job with the given tag and remove it, returns how many jobs were cancelled
+    ///
+    /// Jobs that were already cancelled are removed too, but not counted.
+    pub fn cancel_by_tag(&self, tag: &str) -> usize {
+        self.shared.cancel_by_tag(tag)
+    }
+
+    /// Returns the next `n` run times of all jobs together, in the scheduler timezone
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use tokio_easy_timer::prelude::*;
+    /// let mut s = Scheduler::with_tz(chrono::Utc);
+    /// let a = s.add_job(AsyncJob::new().at_time(3, 0, 0).run(|| async {}));
+    /// let b = s.add_job(AsyncJob::new().at_time(15, 0, 0).run(|| async {}));
+    /// let upcoming = s.upcoming(4);
+    /// assert_eq!(upcoming.len(), 4);
+    /// assert_ne!(upcoming[0].0, upcoming[1].0);
+    /// assert!(upcoming.windows(2).all(|w| w[0].1 <= w[1].1));
+    /// assert_eq!(a.next_run().unwrap().time(), chrono::NaiveTime::from_hms_opt(3, 0, 0).unwrap());
+    /// # let _ = b;
+    /// ```
+    pub fn upcoming(&self, n: usize) -> Vec<(JobId, DateTime<Tz>)> {
+        self.shared.upcoming(n)
+    }
+
+    /// Cancel a job and remove it from the scheduler, returns `false` if there is no such job.
+    pub fn remove(&self, id: JobId) -> bool {
+        self.shared.registry.lock().remove(id)
+    }
+
+    /// Cancel a job and put `job` in its place, the other jobs keep running.
+    ///
+    /// The new job keeps the id of the old one, the returned handle controls it. Returns `None` if there is no such job
+    /// or the scheduler was shut down.
+    pub fn replace(&self, id: JobId, job: BoxedJob<Tz>) -> Option<JobHandle> {
+        self.shared
+            .replace(&mut self.shared.registry.lock(), id, job)
+    }
+}
+
+impl<Tz: chrono::TimeZone> Registry<Tz> {
+    /// Cancel a job and remove it, returns `false` if there is no such job
+    fn remove(&mut self, id: JobId) -> bool {
+        match self.jobs.iter().position(|(_, handle)| handle.id() == id) {
+            Some(i) => {
+                let (_, handle) = self.jobs.remove(i);
+                handle.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl<Tz> Shared<Tz>
+where
+    Tz: TimeZone + Clone + Sync + Send + Copy + 'static,
+    <Tz as TimeZone>::Offset: Send + Sync,
+{
+    fn add(&self, job: BoxedJob<Tz>) -> JobHandle {
+        self.add_locked(&mut self.registry.lock(), job)
+    }
+
+    fn add_locked(&self, registry: &mut Registry<Tz>, job: BoxedJob<Tz>) -> JobHandle {
+        let handle = self.handle(JobId::next(), &job);
+        match registry.state {
+            State::Idle => {}
+            State::Running => self.spawn(&job, &handle),
+            State::Stopped => {
+                handle.cancel();
+                return handle;
+            }
+        }
+        registry.jobs.push((job, handle.clone()));
+        handle
+    }
+
+    fn replace(
+        &self,
+        registry: &mut Registry<Tz>,
+        id: JobId,
+        job: BoxedJob<Tz>,
+    ) -> Option<JobHandle> {
+        let state = registry.state;
+        if state == State::Stopped {
+            return None;
+        }
+        let entry = registry
+            .jobs
+            .iter_mut()
+            .find(|(_, handle)| handle.id() == id)?;
+        entry.1.cancel();
+        let handle = self.handle(id, &job);
+        *entry = (job, handle);
+        if state == State::Running {
+            self.spawn(&entry.0, &entry.1);
+        }
+        Some(entry.1.clone())
+    }
+
+    fn handle(&self, id: JobId, job: &BoxedJob<Tz>) -> JobHandle {
+        let schedules = job.schedules().to_vec();
+        let tz = self.tz;
+        let zone = job.options().timezone;
+        let dst = job.options().dst;
+        let timeline: Timeline = Box::new(move |started, now, n| match zone {
+            Some(zone) => upcoming(&schedules, &zone, dst, started, now, n)
+                .into_iter()
+                .map(|t| t.with_timezone(&t.offset().fix()))
+                .collect(),
+            None => upcoming(&schedules, &tz, dst, started, now, n)
+                .into_iter()
+                .map(|t| t.with_timezone(&t.offset().fix()))
+                .collect(),
+        });
+        JobHandle::new(id, job.options(), self.hooks.clone(), timeline)
+    }
+
+    fn upcoming(&self, n: usize) -> Vec<(JobId, DateTime<Tz>)> {
+        let mut times: Vec<(JobId, DateTime<Tz>)> = self
+            .jobs()
+            .iter()
+            .flat_map(|handle| {
+                let id = handle.id();
+                handle
+                    .upcoming(n)
+                    .into_iter()
+                    .map(move |t| (id, t.with_timezone(&self.tz)))
+            })
+            .collect();
+        times.sort_by(|a, b| a.1.cmp(&b.1));
+        times.truncate(n);
+        times
+    }
+
+    fn jobs(&self) -> Vec<JobHandle> {
+        let registry = self.registry.lock();
+        registry
+            .jobs
+            .iter()
+            .map(|(_, handle)| handle.clone())
+            .collect()
+    }
+
+    fn get(&self, id: JobId) -> Option<JobHandle> {
+        self.jobs().into_iter().find(|handle| handle.id() == id)
+    }
+
+    fn find_by_tag(&self, tag: &str) -> Vec<JobHandle> {
+        self.jobs()
+            .into_iter()
+            .filter(|handle| handle.has_tag(tag))
+            .collect()
+    }
+
+    fn cancel_by_tag(&self, tag: &str) -> usize {
+        let mut registry = self.registry.lock();
+        let mut cancelled = 0;
+        registry.jobs.retain(|(_, handle)| {
+            if !handle.has_tag(tag) {
+                return true;
+            }
+            if handle.status() != JobStatus::Cancelled {
+                handle.cancel();
+                cancelled += 1;
+            }
+            false
+        });
+        cancelled
+    }
+
+    fn spawn(&self, job: &BoxedJob<Tz>, handle: &JobHandle) {
+        let e = self.extensions.clone();
+        let tz = self.tz;
+        let job = job.box_clone();
+        let handle = handle.clone();
+        handle.mark_started();
+        tokio::spawn(async move {
+            let job = job;
+            job.start_schedule(e, tz, handle);
+        });
+    }
 }
@@ -0,0 +1,166 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Weak},
+    time::{Duration, SystemTime},
+};
+
+use chrono::TimeZone;
+
+use super::{BoxedJob, Scheduler, Shared, State};
+use crate::job::{read_config, HandlerRegistry, JobHandle, JobId, JobSpec, SpecError};
+
+/// How often the watched config file is checked for changes
+const POLL: Duration = Duration::from_secs(1);
+
+impl<Tz> Scheduler<Tz>
+where
+    Tz: TimeZone + Clone + Sync + Send + Copy + 'static,
+    <Tz as TimeZone>::Offset: Send + Sync,
+{
+    /// Like `load_config`, then keep the jobs in line with the file while it changes.
+    ///
+    /// The file is checked every second. The jobs are told apart by their name: new jobs are added,
+    /// jobs that are gone are removed and jobs whose definition changed are replaced, the others keep running.
+    /// A file that can not be loaded is logged and leaves every job as it is.
+    ///
+    /// It must be called from within a tokio runtime, the watching stops once the scheduler is shut down or dropped.
+    ///
+    /// ### Example
+    ///
+    /// ```rust,no_run
+    /// # use tokio_easy_timer::prelude::*;
+    /// # use tokio_easy_timer::HandlerRegistry;
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut registry = HandlerRegistry::new();
+    ///     registry.register("cleanup", || async { println!("cleanup") });
+    ///     let mut s = Scheduler::new();
+    ///     s.watch_config("jobs.toml", registry).unwrap();
+    ///     s.run_pending().await;
+    /// }
+    /// ```
+    pub fn watch_config(
+        &mut self,
+        path: impl AsRef<Path>,
+        registry: HandlerRegistry,
+    ) -> Result<Vec<JobHandle>, SpecError> {
+        let path = path.as_ref().to_path_buf();
+        let version = version(&path);
+        let specs = read_config(&path)?;
+        let handles = self.add_specs(&specs, &registry)?;
+        let jobs = specs
+            .into_iter()
+            .zip(handles.iter())
+            .map(|(spec, handle)| (spec.name.clone(), (spec, handle.id())))
+            .collect();
+        let watcher = Watcher {
+            shared: Arc::downgrade(&self.shared),
+            path,
+            registry,
+            version,
+            jobs,
+        };
+        tokio::spawn(watcher.watch());
+        Ok(handles)
+    }
+}
+
+struct Watcher<Tz: TimeZone> {
+    shared: Weak<Shared<Tz>>,
+    path: PathBuf,
+    registry: HandlerRegistry,
+    version: Option<(SystemTime, u64)>,
+    /// The jobs of the file, as they were loaded last time
+    jobs: HashMap<String, (JobSpec, JobId)>,
+}
+
+impl<Tz> Watcher<Tz>
+where
+    Tz: TimeZone + Clone + Sync + Send + Copy + 'static,
+    <Tz as TimeZone>::Offset: Send + Sync,
+{
+    async fn watch(mut self) {
+        loop {
+            tokio::time::sleep(POLL).await;
+            let shared = match self.shared.upgrade() {
+                Some(shared) if shared.registry.lock().state != State::Stopped => shared,
+                _ => return,
+            };
+            let version = version(&self.path);
+            if version != self.version {
+                self.version = version;
+                if let Err(err) = self.reload(&shared) {
+                    log::error!("the jobs are left as they were: {}", err);
+                }
+            }
+        }
+    }
+
+    /// Apply the changes of the file, every new job is built before any is touched
+    fn reload(&mut self, shared: &Shared<Tz>) -> Result<(), SpecError> {
+        let specs = read_config(&self.path)?;
+        let mut changed: Vec<(JobSpec, BoxedJob<Tz>)> = vec![];
+        for spec in specs.iter() {
+            if self.jobs.get(&spec.name).map(|(old, _)| old) != Some(spec) {
+                changed.push((spec.clone(), self.registry.build(spec)?));
+            }
+        }
+        let removed: Vec<String> = self
+            .jobs
+            .keys()
+            .filter(|name| !specs.iter().any(|spec| &spec.name == *name))
+            .cloned()
+            .collect();
+        if changed.is_empty() && removed.is_empty() {
+            return Ok(());
+        }
+
+        let mut registry = shared.registry.lock();
+        if registry.state == State::Stopped {
+            return Ok(());
+        }
+        for name in removed.iter() {
+            if let Some((_, id)) = self.jobs.remove(name) {
+                registry.remove(id);
+            }
+        }
+        let (mut added, mut replaced) = (0, 0);
+        for (spec, job) in changed {
+            // the job may have been removed by hand
+            let old = self
+                .jobs
+                .get(&spec.name)
+                .map(|(_, id)| *id)
+                .filter(|id| registry.jobs.iter().any(|(_, handle)| handle.id() == *id));
+            let handle = match old {
+                Some(id) => {
+                    replaced += 1;
+                    shared
+                        .replace(&mut registry, id, job)
+                        .expect("the job was found under the same lock")
+                }
+                None => {
+                    added += 1;
+                    shared.add_locked(&mut registry, job)
+                }
+            };
+            self.jobs.insert(spec.name.clone(), (spec, handle.id()));
+        }
+        log::info!(
+            "reloaded {}: {} added, {} replaced, {} removed",
+            self.path.display(),
+            added,
+            replaced,
+            removed.len()
+        );
+        Ok(())
+    }
+}
+
+/// Changes whenever the file is written
+fn version(path: &Path) -> Option<(SystemTime, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
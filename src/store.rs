@@ -0,0 +1,73 @@
+use std::{error::Error, fmt};
+
+use chrono::{DateTime, Utc};
+
+use crate::job::JobSpec;
+
+#[cfg(feature = "serde")]
+mod json;
+mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "serde")]
+pub use self::json::JsonFileStore;
+pub use self::memory::MemoryStore;
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::SqliteStore;
+
+/// Remembers when each job last ran, so the runs missed while the process was down can be caught up on startup.
+///
+/// Jobs are stored by the name given with `JobBuilder::name`, the ids change on every start. Jobs without a name are not stored.
+///
+/// A store may also keep jobs defined as data, they are loaded with `Scheduler::load_jobs`.
+pub trait JobStore: Send + Sync {
+    /// Returns the scheduled time of the last successful run of the job
+    fn last_run(&self, name: &str) -> Result<Option<DateTime<Utc>>, StoreError>;
+
+    /// Called after every successful run of the job, with its scheduled time
+    fn set_last_run(&self, name: &str, at: DateTime<Utc>) -> Result<(), StoreError>;
+
+    /// Returns every job kept in the store, ordered by name
+    fn jobs(&self) -> Result<Vec<JobSpec>, StoreError> {
+        Ok(vec![])
+    }
+
+    /// Keep `spec` in the store, it replaces the job with the same name
+    fn save_job(&self, spec: &JobSpec) -> Result<(), StoreError> {
+        let _ = spec;
+        Err(StoreError::new("this store does not keep jobs"))
+    }
+
+    /// Remove the job from the store, returns `false` if there was no such job
+    fn remove_job(&self, name: &str) -> Result<bool, StoreError> {
+        let _ = name;
+        Ok(false)
+    }
+}
+
+/// The error a `JobStore` failed with
+#[derive(Debug)]
+pub struct StoreError {
+    source: Box<dyn Error + Send + Sync>,
+}
+
+impl StoreError {
+    pub fn new(source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self {
+            source: source.into(),
+        }
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "job store: {}", self.source)
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.source)
+    }
+}
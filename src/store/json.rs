@@ -0,0 +1,103 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::{JobStore, StoreError};
+use crate::job::JobSpec;
+
+#[derive(Default, Serialize, Deserialize)]
+struct Content {
+    #[serde(default)]
+    last_runs: BTreeMap<String, DateTime<Utc>>,
+    #[serde(default)]
+    jobs: BTreeMap<String, JobSpec>,
+}
+
+/// A `JobStore` that keeps everything in a JSON file, the file is rewritten on every change.
+///
+/// ### Example
+///
+/// ```rust,no_run
+/// # use tokio_easy_timer::prelude::*;
+/// # use tokio_easy_timer::JsonFileStore;
+/// let mut s = Scheduler::new();
+/// s.set_store(JsonFileStore::open("jobs.json").unwrap());
+/// s.add(
+///     SyncJob::new()
+///         .name("backup")
+///         .at(3.hours())
+///         .misfire(MisfirePolicy::FireOnce)
+///         .run(|| println!("backup")),
+/// );
+/// ```
+pub struct JsonFileStore {
+    path: PathBuf,
+    content: Mutex<Content>,
+}
+
+impl JsonFileStore {
+    /// Read the store from `path`, the file is created on the first change if it does not exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let path = path.as_ref().to_path_buf();
+        let content = match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(StoreError::new)?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Content::default(),
+            Err(err) => return Err(StoreError::new(err)),
+        };
+        Ok(Self {
+            path,
+            content: Mutex::new(content),
+        })
+    }
+
+    fn save(&self, content: &Content) -> Result<(), StoreError> {
+        let bytes = serde_json::to_vec_pretty(content).map_err(StoreError::new)?;
+        // write aside and rename, so the file is never left half written
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, bytes).map_err(StoreError::new)?;
+        fs::rename(&tmp, &self.path).map_err(StoreError::new)
+    }
+}
+
+impl JobStore for JsonFileStore {
+    fn last_run(&self, name: &str) -> Result<Option<DateTime<Utc>>, StoreError> {
+        Ok(self.content.lock().last_runs.get(name).copied())
+    }
+
+    fn set_last_run(&self, name: &str, at: DateTime<Utc>) -> Result<(), StoreError> {
+        let mut content = self.content.lock();
+        let last = content.last_runs.entry(name.to_string()).or_insert(at);
+        // runs may complete out of order
+        if *last > at {
+            return Ok(());
+        }
+        *last = at;
+        self.save(&content)
+    }
+
+    fn jobs(&self) -> Result<Vec<JobSpec>, StoreError> {
+        Ok(self.content.lock().jobs.values().cloned().collect())
+    }
+
+    fn save_job(&self, spec: &JobSpec) -> Result<(), StoreError> {
+        let mut content = self.content.lock();
+        content.jobs.insert(spec.name.clone(), spec.clone());
+        self.save(&content)
+    }
+
+    fn remove_job(&self, name: &str) -> Result<bool, StoreError> {
+        let mut content = self.content.lock();
+        if content.jobs.remove(name).is_none() {
+            return Ok(false);
+        }
+        self.save(&content)?;
+        Ok(true)
+    }
+}
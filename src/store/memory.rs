@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+
+use super::{JobStore, StoreError};
+use crate::job::JobSpec;
+
+/// A `JobStore` that keeps everything in memory, it is lost when the process exits
+#[derive(Default)]
+pub struct MemoryStore {
+    last_runs: Mutex<HashMap<String, DateTime<Utc>>>,
+    jobs: Mutex<std::collections::BTreeMap<String, JobSpec>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl JobStore for MemoryStore {
+    fn last_run(&self, name: &str) -> Result<Option<DateTime<Utc>>, StoreError> {
+        Ok(self.last_runs.lock().get(name).copied())
+    }
+
+    fn set_last_run(&self, name: &str, at: DateTime<Utc>) -> Result<(), StoreError> {
+        let mut last_runs = self.last_runs.lock();
+        let last = last_runs.entry(name.to_string()).or_insert(at);
+        *last = at.max(*last);
+        Ok(())
+    }
+
+    fn jobs(&self) -> Result<Vec<JobSpec>, StoreError> {
+        Ok(self.jobs.lock().values().cloned().collect())
+    }
+
+    fn save_job(&self, spec: &JobSpec) -> Result<(), StoreError> {
+        self.jobs.lock().insert(spec.name.clone(), spec.clone());
+        Ok(())
+    }
+
+    fn remove_job(&self, name: &str) -> Result<bool, StoreError> {
+        Ok(self.jobs.lock().remove(name).is_some())
+    }
+}
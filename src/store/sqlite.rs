@@ -0,0 +1,223 @@
+use std::{path::Path, time::Duration};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{JobStore, StoreError};
+use crate::job::JobSpec;
+
+/// How `JobSpec::since` is kept, it parses back as a `NaiveDateTime`
+const SINCE: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// A `JobStore` backed by a SQLite database, the tables are created when the store is opened.
+///
+/// The args, tags and policies of a job are kept as JSON text, the durations in milliseconds and the timezone by its name.
+///
+/// ### Example
+///
+/// ```rust,no_run
+/// # use tokio_easy_timer::prelude::*;
+/// # use tokio_easy_timer::{HandlerRegistry, JobStore, JobSpec, SqliteStore};
+/// # async fn send_digest() {}
+/// let store = SqliteStore::open("jobs.db").unwrap();
+/// store
+///     .save_job(&JobSpec::new("digest", "0 0 8 * * *", "send_digest"))
+///     .unwrap();
+///
+/// let mut registry = HandlerRegistry::new();
+/// registry.register("send_digest", send_digest);
+/// let mut s = Scheduler::new();
+/// s.load_jobs(&store, &registry).unwrap();
+/// s.set_store(store);
+/// ```
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Open the database at `path`, it is created if it does not exist
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, StoreError> {
+        Self::new(Connection::open(path).map_err(StoreError::new)?)
+    }
+
+    /// Open a database that only lives in memory
+    pub fn open_in_memory() -> Result<Self, StoreError> {
+        Self::new(Connection::open_in_memory().map_err(StoreError::new)?)
+    }
+
+    fn new(conn: Connection) -> Result<Self, StoreError> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS last_runs (
+                name TEXT PRIMARY KEY,
+                at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS jobs (
+                name TEXT PRIMARY KEY,
+                cron TEXT NOT NULL,
+                handler TEXT NOT NULL,
+                args TEXT NOT NULL,
+                tags TEXT NOT NULL,
+                repeat INTEGER NOT NULL,
+                timeout_ms INTEGER,
+                concurrency TEXT NOT NULL,
+                misfire TEXT NOT NULL,
+                retry TEXT NOT NULL,
+                timezone TEXT,
+                every_ms INTEGER,
+                fixed_delay_ms INTEGER,
+                interval_ms INTEGER NOT NULL,
+                since TEXT,
+                delay_ms INTEGER NOT NULL,
+                dst TEXT NOT NULL
+            );",
+        )
+        .map_err(StoreError::new)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+impl JobStore for SqliteStore {
+    fn last_run(&self, name: &str) -> Result<Option<DateTime<Utc>>, StoreError> {
+        let at: Option<String> = self
+            .conn
+            .lock()
+            .query_row(
+                "SELECT at FROM last_runs WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(StoreError::new)?;
+        at.map(|at| {
+            DateTime::parse_from_rfc3339(&at)
+                .map(|at| at.with_timezone(&Utc))
+                .map_err(StoreError::new)
+        })
+        .transpose()
+    }
+
+    fn set_last_run(&self, name: &str, at: DateTime<Utc>) -> Result<(), StoreError> {
+        // runs may complete out of order, rfc3339 times in utc sort as text
+        self.conn
+            .lock()
+            .execute(
+                "INSERT INTO last_runs (name, at) VALUES (?1, ?2)
+                ON CONFLICT (name) DO UPDATE SET at = excluded.at WHERE excluded.at > at",
+                params![name, at.to_rfc3339()],
+            )
+            .map_err(StoreError::new)?;
+        Ok(())
+    }
+
+    fn jobs(&self) -> Result<Vec<JobSpec>, StoreError> {
+        let conn = self.conn.lock();
+        let mut stmt = conn
+            .prepare(
+                "SELECT name, cron, handler, repeat, timeout_ms, args, tags, concurrency, misfire, retry, timezone,
+                every_ms, fixed_delay_ms, interval_ms, since, delay_ms, dst
+                FROM jobs ORDER BY name",
+            )
+            .map_err(StoreError::new)?;
+        let rows = stmt
+            .query_map([], |row| {
+                let spec = JobSpec {
+                    repeat: row.get(3)?,
+                    timeout: row.get::<_, Option<u64>>(4)?.map(Duration::from_millis),
+                    every: row.get::<_, Option<u64>>(11)?.map(Duration::from_millis),
+                    fixed_delay: row.get::<_, Option<u64>>(12)?.map(Duration::from_millis),
+                    interval: Duration::from_millis(row.get(13)?),
+                    delay: Duration::from_millis(row.get(15)?),
+                    ..JobSpec::new(
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                    )
+                };
+                let json: [String; 6] = [
+                    row.get(5)?,
+                    row.get(6)?,
+                    row.get(7)?,
+                    row.get(8)?,
+                    row.get(9)?,
+                    row.get(16)?,
+                ];
+                let texts: [Option<String>; 2] = [row.get(10)?, row.get(14)?];
+                Ok((spec, json, texts))
+            })
+            .map_err(StoreError::new)?;
+        rows.map(|row| {
+            let (mut spec, [args, tags, concurrency, misfire, retry, dst], [timezone, since]) =
+                row.map_err(StoreError::new)?;
+            spec.args = from_json(&args)?;
+            spec.tags = from_json(&tags)?;
+            spec.concurrency = from_json(&concurrency)?;
+            spec.misfire = from_json(&misfire)?;
+            spec.retry = from_json(&retry)?;
+            spec.dst = from_json(&dst)?;
+            spec.timezone = timezone
+                .map(|tz| tz.parse())
+                .transpose()
+                .map_err(StoreError::new)?;
+            spec.since = since
+                .map(|since| since.parse())
+                .transpose()
+                .map_err(StoreError::new)?;
+            Ok(spec)
+        })
+        .collect()
+    }
+
+    fn save_job(&self, spec: &JobSpec) -> Result<(), StoreError> {
+        let millis = |d: Duration| d.as_millis() as u64;
+        self.conn
+            .lock()
+            .execute(
+                "INSERT OR REPLACE INTO jobs
+                (name, cron, handler, repeat, timeout_ms, args, tags, concurrency, misfire, retry, timezone,
+                every_ms, fixed_delay_ms, interval_ms, since, delay_ms, dst)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+                params![
+                    spec.name,
+                    spec.cron,
+                    spec.handler,
+                    spec.repeat,
+                    spec.timeout.map(millis),
+                    to_json(&spec.args)?,
+                    to_json(&spec.tags)?,
+                    to_json(&spec.concurrency)?,
+                    to_json(&spec.misfire)?,
+                    to_json(&spec.retry)?,
+                    spec.timezone.map(|tz| tz.to_string()),
+                    spec.every.map(millis),
+                    spec.fixed_delay.map(millis),
+                    millis(spec.interval),
+                    spec.since.map(|since| since.format(SINCE).to_string()),
+                    millis(spec.delay),
+                    to_json(&spec.dst)?,
+                ],
+            )
+            .map_err(StoreError::new)?;
+        Ok(())
+    }
+
+    fn remove_job(&self, name: &str) -> Result<bool, StoreError> {
+        let removed = self
+            .conn
+            .lock()
+            .execute("DELETE FROM jobs WHERE name = ?1", params![name])
+            .map_err(StoreError::new)?;
+        Ok(removed > 0)
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, StoreError> {
+    serde_json::to_string(value).map_err(StoreError::new)
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, StoreError> {
+    serde_json::from_str(json).map_err(StoreError::new)
+}
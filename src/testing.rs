@@ -0,0 +1,144 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use parking_lot::Mutex;
+
+use crate::{MockClock, RunRecord, Scheduler};
+
+/// A scheduler on a `MockClock` that keeps every finished call of a handler, to test schedules without waiting.
+///
+/// It needs a paused tokio clock, such as the one of `#[tokio::test(start_paused = true)]`, which the `testing` feature enables.
+/// Jobs are added through the scheduler it derefs to. Calls are recorded once the handler returns: a `SyncJob` handler runs
+/// on the blocking thread pool in real time, `run_for` waits for it.
+///
+/// ### Example
+///
+/// ```rust
+/// # use chrono::{TimeZone, Utc};
+/// # use std::time::Duration;
+/// # use tokio_easy_timer::prelude::*;
+/// # use tokio_easy_timer::RecordingScheduler;
+/// # #[tokio::main(flavor = "current_thread", start_paused = true)]
+/// # async fn main() {
+/// let mut s = RecordingScheduler::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+/// s.add(
+///     AsyncJob::new()
+///         .name("report")
+///         .at(Interval::Monday)
+///         .at_time(3, 0, 0)
+///         .run(|| async {}),
+/// );
+///
+/// s.run_for(Duration::from_secs(14 * 24 * 3600)).await;
+/// s.assert_fired_at(
+///     "report",
+///     [1, 8].map(|day| Utc.with_ymd_and_hms(2024, 1, day, 3, 0, 0).unwrap()),
+/// );
+/// assert!(s.runs().iter().all(|run| run.outcome.is_success()));
+/// # }
+/// ```
+pub struct RecordingScheduler<Tz = Utc>
+where
+    Tz: TimeZone,
+{
+    scheduler: Scheduler<Tz>,
+    clock: MockClock,
+    runs: Arc<Mutex<Vec<RunRecord>>>,
+}
+
+impl RecordingScheduler {
+    /// A scheduler in UTC whose clock reads `start`
+    pub fn new(start: DateTime<Utc>) -> RecordingScheduler {
+        RecordingScheduler::with_tz(Utc, start)
+    }
+}
+
+impl<Tz> RecordingScheduler<Tz>
+where
+    Tz: TimeZone + Clone + Sync + Send + Copy + 'static,
+    <Tz as TimeZone>::Offset: Send + Sync,
+{
+    /// A scheduler in `tz` whose clock reads `start`
+    pub fn with_tz(tz: Tz, start: DateTime<Utc>) -> Self {
+        let scheduler = Scheduler::with_tz(tz);
+        let clock = MockClock::new(start);
+        scheduler.set_clock(clock.clone());
+        let runs: Arc<Mutex<Vec<RunRecord>>> = Arc::default();
+        let recorded = runs.clone();
+        scheduler.on_run(move |record| recorded.lock().push(record.clone()));
+        Self {
+            scheduler,
+            clock,
+            runs,
+        }
+    }
+
+    /// Returns the clock of the scheduler
+    pub fn clock(&self) -> &MockClock {
+        &self.clock
+    }
+
+    /// Start the scheduler if it is not running yet, then let `d` go by on its clock
+    ///
+    /// The paused clock does not move on while a call runs on the blocking thread pool, `run_for` returns once the sync
+    /// handlers and store accesses that started within `d` are over.
+    pub async fn run_for(&self, d: Duration) {
+        self.scheduler.run().await;
+        self.clock.advance(d).await;
+        self.scheduler.blocking_idle().await;
+    }
+
+    /// Returns every finished call of a handler, in the order they finished
+    pub fn runs(&self) -> Vec<RunRecord> {
+        self.runs.lock().clone()
+    }
+
+    /// Returns the times the job named `job` was scheduled at, retries left out, in order
+    pub fn fired_at(&self, job: &str) -> Vec<DateTime<FixedOffset>> {
+        let mut times: Vec<DateTime<FixedOffset>> = self
+            .runs
+            .lock()
+            .iter()
+            .filter(|record| record.run.name.as_deref() == Some(job) && record.run.attempt == 1)
+            .map(|record| record.run.scheduled_at)
+            .collect();
+        times.sort();
+        times
+    }
+
+    /// Panics unless the job named `job` was scheduled at exactly `times`, in order
+    #[track_caller]
+    pub fn assert_fired_at<T, I>(&self, job: &str, times: I)
+    where
+        T: TimeZone,
+        I: IntoIterator<Item = DateTime<T>>,
+    {
+        let fired = self.fired_at(job);
+        let expected: Vec<DateTime<T>> = times.into_iter().collect();
+        let same =
+            fired.len() == expected.len() && fired.iter().zip(expected.iter()).all(|(a, b)| a == b);
+        assert!(
+            same,
+            "job {} fired at {:?}, expected {:?}",
+            job, fired, expected
+        );
+    }
+}
+
+impl<Tz: TimeZone> Deref for RecordingScheduler<Tz> {
+    type Target = Scheduler<Tz>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.scheduler
+    }
+}
+
+impl<Tz: TimeZone> DerefMut for RecordingScheduler<Tz> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.scheduler
+    }
+}
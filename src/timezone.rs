@@ -0,0 +1,196 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+
+use crate::job::ScheduleError;
+
+/// A timezone picked at runtime, a job given one with `JobBuilder::timezone` evaluates its schedule there
+/// instead of in the timezone of the scheduler.
+///
+/// It is parsed from `UTC`, `Local`, an offset such as `+09:00` or, with the `chrono-tz` feature, an IANA name such as `Europe/Berlin`.
+///
+/// ### Example
+///
+/// ```rust
+/// # use tokio_easy_timer::JobTz;
+/// let tz: JobTz = "+09:00".parse().unwrap();
+/// assert_eq!(tz, JobTz::Fixed(chrono::FixedOffset::east_opt(9 * 3600).unwrap()));
+/// assert_eq!("utc".parse::<JobTz>().unwrap(), JobTz::Utc);
+/// assert!("Mars/Olympus_Mons".parse::<JobTz>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobTz {
+    Utc,
+    /// The timezone of the machine
+    Local,
+    Fixed(FixedOffset),
+    /// A timezone of the IANA database, it follows its daylight saving rules
+    #[cfg(feature = "chrono-tz")]
+    Named(chrono_tz::Tz),
+}
+
+/// The offset of a `JobTz` at some time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobTzOffset {
+    tz: JobTz,
+    fixed: FixedOffset,
+}
+
+impl Offset for JobTzOffset {
+    fn fix(&self) -> FixedOffset {
+        self.fixed
+    }
+}
+
+impl fmt::Display for JobTzOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fixed.fmt(f)
+    }
+}
+
+impl JobTz {
+    fn offset(self, fixed: impl Offset) -> JobTzOffset {
+        JobTzOffset {
+            tz: self,
+            fixed: fixed.fix(),
+        }
+    }
+}
+
+impl TimeZone for JobTz {
+    type Offset = JobTzOffset;
+
+    fn from_offset(offset: &JobTzOffset) -> Self {
+        offset.tz
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<JobTzOffset> {
+        match *self {
+            JobTz::Utc => LocalResult::Single(self.offset(Utc.fix())),
+            JobTz::Local => Local.offset_from_local_date(local).map(|o| self.offset(o)),
+            JobTz::Fixed(o) => LocalResult::Single(self.offset(o)),
+            #[cfg(feature = "chrono-tz")]
+            JobTz::Named(tz) => tz.offset_from_local_date(local).map(|o| self.offset(o)),
+        }
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<JobTzOffset> {
+        match *self {
+            JobTz::Utc => LocalResult::Single(self.offset(Utc.fix())),
+            JobTz::Local => Local
+                .offset_from_local_datetime(local)
+                .map(|o| self.offset(o)),
+            JobTz::Fixed(o) => LocalResult::Single(self.offset(o)),
+            #[cfg(feature = "chrono-tz")]
+            JobTz::Named(tz) => tz.offset_from_local_datetime(local).map(|o| self.offset(o)),
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> JobTzOffset {
+        match *self {
+            JobTz::Utc => self.offset(Utc.fix()),
+            JobTz::Local => self.offset(Local.offset_from_utc_date(utc)),
+            JobTz::Fixed(o) => self.offset(o),
+            #[cfg(feature = "chrono-tz")]
+            JobTz::Named(tz) => self.offset(tz.offset_from_utc_date(utc)),
+        }
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> JobTzOffset {
+        match *self {
+            JobTz::Utc => self.offset(Utc.fix()),
+            JobTz::Local => self.offset(Local.offset_from_utc_datetime(utc)),
+            JobTz::Fixed(o) => self.offset(o),
+            #[cfg(feature = "chrono-tz")]
+            JobTz::Named(tz) => self.offset(tz.offset_from_utc_datetime(utc)),
+        }
+    }
+}
+
+impl FromStr for JobTz {
+    type Err = ScheduleError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "UTC" | "utc" | "Z" => return Ok(JobTz::Utc),
+            "Local" | "local" => return Ok(JobTz::Local),
+            _ => {}
+        }
+        if let Some(offset) = parse_offset(name) {
+            return Ok(JobTz::Fixed(offset));
+        }
+        #[cfg(feature = "chrono-tz")]
+        if let Ok(tz) = name.parse::<chrono_tz::Tz>() {
+            return Ok(JobTz::Named(tz));
+        }
+        Err(ScheduleError::UnknownTimezone(name.to_string()))
+    }
+}
+
+/// Parse an offset such as `+09:00` or `-0530`
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let sign = match s.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits: String = s[1..].chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+impl fmt::Display for JobTz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobTz::Utc => write!(f, "UTC"),
+            JobTz::Local => write!(f, "Local"),
+            JobTz::Fixed(offset) => offset.fmt(f),
+            #[cfg(feature = "chrono-tz")]
+            JobTz::Named(tz) => write!(f, "{}", tz.name()),
+        }
+    }
+}
+
+impl From<Utc> for JobTz {
+    fn from(_: Utc) -> Self {
+        JobTz::Utc
+    }
+}
+
+impl From<Local> for JobTz {
+    fn from(_: Local) -> Self {
+        JobTz::Local
+    }
+}
+
+impl From<FixedOffset> for JobTz {
+    fn from(offset: FixedOffset) -> Self {
+        JobTz::Fixed(offset)
+    }
+}
+
+#[cfg(feature = "chrono-tz")]
+impl From<chrono_tz::Tz> for JobTz {
+    fn from(tz: chrono_tz::Tz) -> Self {
+        JobTz::Named(tz)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for JobTz {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for JobTz {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(d)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::{Clock, MockClock};
+
+mod common;
+
+use common::utc;
+
+#[tokio::test(start_paused = true)]
+async fn the_clock_moves_with_the_paused_tokio_clock() {
+    let clock = MockClock::new(utc(1, 1, 0, 0));
+    assert_eq!(clock.now(), utc(1, 1, 0, 0));
+
+    clock.advance(Duration::from_secs(90)).await;
+    assert_eq!(
+        clock.now(),
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 1, 30).unwrap()
+    );
+
+    // the clones share the time
+    let other = clock.clone();
+    other.set(utc(1, 5, 12, 0));
+    assert_eq!(clock.now(), utc(1, 5, 12, 0));
+    clock.advance(Duration::from_millis(250)).await;
+    assert_eq!(
+        other.now(),
+        utc(1, 5, 12, 0) + chrono::Duration::milliseconds(250)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_scheduler_reads_the_time_from_the_clock() {
+    let clock = MockClock::new(utc(1, 1, 0, 0));
+    let mut s = Scheduler::with_tz(Utc);
+    s.set_clock(clock.clone());
+    let job = s.add_job(AsyncJob::new().at_time(3, 0, 0).run(|| async {}));
+    assert_eq!(job.upcoming(2), [utc(1, 1, 3, 0), utc(1, 2, 3, 0)]);
+
+    clock.set(utc(1, 5, 12, 0));
+    assert_eq!(job.upcoming(2), [utc(1, 6, 3, 0), utc(1, 7, 3, 0)]);
+
+    s.run().await;
+    clock.advance(Duration::from_secs(24 * 3600)).await;
+    let fired: Vec<_> = job.history().iter().map(|r| r.run.scheduled_at).collect();
+    assert_eq!(fired, [utc(1, 6, 3, 0)]);
+}
@@ -0,0 +1,99 @@
+//! Helpers shared by the integration tests, each test uses a part of them
+#![allow(dead_code, unused_macros)]
+
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+#[cfg(feature = "testing")]
+use tokio_easy_timer::RecordingScheduler;
+
+pub const MINUTE: Duration = Duration::from_secs(60);
+pub const HOUR: Duration = Duration::from_secs(3600);
+pub const DAY: Duration = Duration::from_secs(24 * 3600);
+
+/// `m` minutes and `s` seconds past midnight of January 1, 2024
+pub fn at(m: u32, s: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, m, s).unwrap()
+}
+
+/// `h:m` on January 1, 2024
+pub fn at_hm(h: u32, m: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, h, m, 0).unwrap()
+}
+
+/// `h:min` on day `d` of month `m` of 2024
+pub fn utc(m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, m, d, h, min, 0).unwrap()
+}
+
+/// The scheduled, start and end times of every finished run of `job`
+#[cfg(feature = "testing")]
+pub fn runs_of(
+    s: &RecordingScheduler,
+    job: &str,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>, DateTime<Utc>)> {
+    s.runs()
+        .iter()
+        .filter(|record| record.run.name.as_deref() == Some(job))
+        .map(|record| {
+            (
+                record.run.scheduled_at.with_timezone(&Utc),
+                record.started_at.with_timezone(&Utc),
+                record.finished_at.with_timezone(&Utc),
+            )
+        })
+        .collect()
+}
+
+/// The attempt number and start time of every call of the handler of `job`
+#[cfg(feature = "testing")]
+pub fn attempts(s: &RecordingScheduler, job: &str) -> Vec<(u32, DateTime<Utc>)> {
+    s.runs()
+        .iter()
+        .filter(|record| record.run.name.as_deref() == Some(job))
+        .map(|record| (record.run.attempt, record.started_at.with_timezone(&Utc)))
+        .collect()
+}
+
+/// A job due at minutes 1, 2 and 3 whose handler takes 130 seconds
+macro_rules! overlapping {
+    ($name:expr, $policy:expr) => {
+        tokio_easy_timer::AsyncJob::new()
+            .name($name)
+            .cron("0 1,2,3 * * * *")
+            .concurrency($policy)
+            .run(|| async { tokio::time::sleep(std::time::Duration::from_secs(130)).await })
+    };
+}
+
+/// A job every minute whose handler takes `secs` seconds
+macro_rules! slow {
+    ($name:expr, $secs:expr) => {
+        tokio_easy_timer::AsyncJob::new()
+            .name($name)
+            .cron("0 * * * * *")
+            .run(|| async { tokio::time::sleep(std::time::Duration::from_secs($secs)).await })
+    };
+}
+
+/// A job at the start of every hour with the given misfire policy
+macro_rules! hourly {
+    ($name:expr, $policy:expr) => {
+        tokio_easy_timer::AsyncJob::new()
+            .name($name)
+            .cron("0 * * * *")
+            .misfire($policy)
+            .run(|| async {})
+    };
+}
+
+/// A job named `name` at `cron` in Berlin time, with the given daylight saving policies
+macro_rules! berlin {
+    ($name:expr, $cron:expr $(, $policy:expr)*) => {
+        tokio_easy_timer::AsyncJob::new()
+            .name($name)
+            .cron($cron)
+            $(.dst($policy))*
+            .run(|| async {})
+    };
+}
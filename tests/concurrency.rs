@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::RecordingScheduler;
+
+#[macro_use]
+mod common;
+
+use common::{at, runs_of};
+
+#[tokio::test(start_paused = true)]
+async fn the_policy_decides_what_happens_to_overlapping_runs() {
+    let mut s = RecordingScheduler::new(at(0, 30));
+    s.add(overlapping!("allow", ConcurrencyPolicy::Allow))
+        .add(overlapping!("skip", ConcurrencyPolicy::Skip))
+        .add(overlapping!("queue", ConcurrencyPolicy::Queue(1)))
+        .add(overlapping!("replace", ConcurrencyPolicy::Replace));
+    s.run_for(Duration::from_secs(6 * 60 + 15)).await;
+
+    assert_eq!(
+        runs_of(&s, "allow"),
+        [
+            (at(1, 0), at(1, 0), at(3, 10)),
+            (at(2, 0), at(2, 0), at(4, 10)),
+            (at(3, 0), at(3, 0), at(5, 10)),
+        ]
+    );
+    // the ticks at 2 and 3 come while the first run is going
+    assert_eq!(runs_of(&s, "skip"), [(at(1, 0), at(1, 0), at(3, 10))]);
+    // the tick at 2 waits for the first run, there is no room left for the one at 3
+    assert_eq!(
+        runs_of(&s, "queue"),
+        [
+            (at(1, 0), at(1, 0), at(3, 10)),
+            (at(2, 0), at(3, 10), at(5, 20)),
+        ]
+    );
+    // every tick aborts the run before it
+    assert_eq!(runs_of(&s, "replace"), [(at(3, 0), at(3, 0), at(5, 10))]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_replaced_run_is_no_longer_in_flight() {
+    let mut s = RecordingScheduler::new(at(0, 30));
+    let handle = s.add_job(overlapping!("replace", ConcurrencyPolicy::Replace));
+    s.run_for(Duration::from_secs(100)).await;
+
+    let in_flight: Vec<_> = handle
+        .in_flight()
+        .iter()
+        .map(|run| run.scheduled_at)
+        .collect();
+    assert_eq!(in_flight, [at(2, 0)]);
+    assert!(s.runs().is_empty());
+}
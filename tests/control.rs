@@ -0,0 +1,57 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::RecordingScheduler;
+
+mod common;
+
+use common::{at, MINUTE};
+
+#[tokio::test(start_paused = true)]
+async fn a_paused_job_skips_its_ticks_until_it_is_resumed() {
+    let mut s = RecordingScheduler::new(at(0, 0));
+    let job = s.add_job(
+        AsyncJob::new()
+            .name("minutely")
+            .cron("0 * * * * *")
+            .run(|| async {}),
+    );
+    s.run_for(2 * MINUTE + MINUTE / 2).await;
+
+    job.pause();
+    assert_eq!(job.status(), JobStatus::Paused);
+    s.run_for(2 * MINUTE).await;
+    s.assert_fired_at("minutely", [at(1, 0), at(2, 0)]);
+
+    job.resume();
+    assert_eq!(job.status(), JobStatus::Running);
+    s.run_for(2 * MINUTE).await;
+    s.assert_fired_at("minutely", [at(1, 0), at(2, 0), at(5, 0), at(6, 0)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancel_aborts_a_running_handler() {
+    static FINISHED: AtomicBool = AtomicBool::new(false);
+    let mut s = RecordingScheduler::new(at(0, 0));
+    let job = s.add_job(
+        AsyncJob::new()
+            .name("long")
+            .cron("0 * * * * *")
+            .run(|| async {
+                tokio::time::sleep(Duration::from_secs(50)).await;
+                FINISHED.store(true, Ordering::SeqCst);
+            }),
+    );
+    s.run_for(MINUTE + MINUTE / 2).await;
+    assert_eq!(job.in_flight().len(), 1);
+
+    job.cancel();
+    assert_eq!(job.status(), JobStatus::Cancelled);
+    s.run_for(3 * MINUTE).await;
+    assert!(job.in_flight().is_empty());
+    assert!(!FINISHED.load(Ordering::SeqCst));
+    assert!(s.runs().is_empty());
+}
@@ -0,0 +1,73 @@
+use chrono_tz::Europe::Berlin;
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::{DstPolicy, RecordingScheduler};
+
+#[macro_use]
+mod common;
+
+use common::{utc, HOUR};
+
+#[tokio::test(start_paused = true)]
+async fn a_skipped_local_time_is_shifted_or_skipped() {
+    // 00:45 winter time, the clocks move from 02:00 to 03:00 on March 31
+    let mut s = RecordingScheduler::with_tz(Berlin, utc(3, 30, 23, 45));
+    s.add(berlin!("shift", "0 30 2 * * *"))
+        .add(berlin!("skip", "0 30 2 * * *", DstPolicy::Skip))
+        .add(berlin!("hourly", "0 30 * * * *"));
+    s.run_for(25 * HOUR).await;
+
+    // 02:30 is shifted to 03:30 summer time, then runs at 02:30 summer time
+    s.assert_fired_at("shift", [utc(3, 31, 1, 30), utc(4, 1, 0, 30)]);
+    s.assert_fired_at("skip", [utc(4, 1, 0, 30)]);
+    // the shifted 02:30 and 03:30 are the same run
+    let hourly = s.fired_at("hourly");
+    assert_eq!(
+        hourly[..3],
+        [utc(3, 31, 0, 30), utc(3, 31, 1, 30), utc(3, 31, 2, 30)]
+    );
+    assert_eq!(hourly.len(), 25);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_repeated_local_time_runs_once_or_twice() {
+    // 00:45 summer time, the clocks move from 03:00 back to 02:00 on October 27
+    let mut s = RecordingScheduler::with_tz(Berlin, utc(10, 26, 22, 45));
+    s.add(berlin!("once", "0 30 2 * * *"))
+        .add(berlin!("both", "0 30 2 * * *", DstPolicy::RunBoth))
+        .add(berlin!("hourly", "0 30 * * * *"))
+        .add(berlin!("hourly-both", "0 30 * * * *", DstPolicy::RunBoth));
+    s.run_for(4 * HOUR).await;
+
+    s.assert_fired_at("once", [utc(10, 27, 0, 30)]);
+    let both: Vec<String> = s.fired_at("both").iter().map(|t| t.to_rfc3339()).collect();
+    assert_eq!(
+        both,
+        ["2024-10-27T02:30:00+02:00", "2024-10-27T02:30:00+01:00"]
+    );
+    let hourly = [(10, 26, 23), (10, 27, 0), (10, 27, 2)];
+    s.assert_fired_at("hourly", hourly.map(|(m, d, h)| utc(m, d, h, 30)));
+    let hourly_both = [(10, 26, 23), (10, 27, 0), (10, 27, 1), (10, 27, 2)];
+    s.assert_fired_at("hourly-both", hourly_both.map(|(m, d, h)| utc(m, d, h, 30)));
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_policies_for_both_transitions_can_be_combined() {
+    for (start, run, fired) in [
+        (utc(3, 30, 23, 45), 25 * HOUR, vec![utc(4, 1, 0, 30)]),
+        (
+            utc(10, 26, 22, 45),
+            4 * HOUR,
+            vec![utc(10, 27, 0, 30), utc(10, 27, 1, 30)],
+        ),
+    ] {
+        let mut s = RecordingScheduler::with_tz(Berlin, start);
+        s.add(berlin!(
+            "night",
+            "0 30 2 * * *",
+            DstPolicy::RunBoth,
+            DstPolicy::Skip
+        ));
+        s.run_for(run).await;
+        s.assert_fired_at("night", fired);
+    }
+}
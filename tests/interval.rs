@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::RecordingScheduler;
+
+mod common;
+
+use common::{at, MINUTE};
+
+/// A handler that takes 20 seconds
+async fn slow() {
+    tokio::time::sleep(Duration::from_secs(20)).await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_fixed_rate_is_counted_from_the_start_of_the_job() {
+    let mut s = RecordingScheduler::new(at(0, 0));
+    s.add(
+        AsyncJob::new()
+            .name("rate")
+            .every_duration(MINUTE)
+            .run(slow),
+    );
+    s.run_for(3 * MINUTE + MINUTE / 2).await;
+
+    s.assert_fired_at("rate", [at(1, 0), at(2, 0), at(3, 0)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_fixed_delay_is_counted_from_the_end_of_the_previous_run() {
+    let mut s = RecordingScheduler::new(at(0, 0));
+    s.add(
+        AsyncJob::new()
+            .name("delay")
+            .with_fixed_delay(MINUTE)
+            .run(slow),
+    );
+    s.run_for(4 * MINUTE + MINUTE / 2).await;
+
+    // every run takes 20 seconds, the next one starts a minute after it ended
+    s.assert_fired_at("delay", [at(1, 0), at(2, 20), at(3, 40)]);
+}
@@ -0,0 +1,121 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::{Misfire, RecordingScheduler};
+
+mod common;
+
+use common::{at_hm, HOUR};
+
+/// Add an hourly job named `job`, returns the misfires passed to its hook
+fn hourly(
+    s: &mut RecordingScheduler,
+    job: &str,
+    policy: MisfirePolicy,
+) -> Arc<Mutex<Vec<Misfire>>> {
+    let misfires: Arc<Mutex<Vec<Misfire>>> = Arc::default();
+    let seen = misfires.clone();
+    s.add(
+        AsyncJob::new()
+            .name(job)
+            .cron("0 * * * *")
+            .misfire(policy)
+            .on_misfire(move |_, misfire| seen.lock().unwrap().push(misfire.clone()))
+            .run(|| async {}),
+    );
+    misfires
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_tick_slept_through_is_a_misfire() {
+    let mut s = RecordingScheduler::new(at_hm(2, 0));
+    let misfires: Arc<Mutex<Vec<Misfire>>> = Arc::default();
+    let seen = misfires.clone();
+    s.add(
+        AsyncJob::new()
+            .name("backup")
+            .at_time(3, 0, 0)
+            .misfire(MisfirePolicy::FireIfWithin(Duration::from_secs(60)))
+            .on_misfire(move |_, misfire| seen.lock().unwrap().push(misfire.clone()))
+            .run(|| async {}),
+    );
+    s.run_for(HOUR / 2).await;
+
+    // the machine sleeps for 5 hours, the timer of the job does not notice
+    s.clock().set(at_hm(7, 30));
+    s.run_for(HOUR).await;
+
+    s.assert_fired_at("backup", Vec::<DateTime<Utc>>::new());
+    assert_eq!(
+        *misfires.lock().unwrap(),
+        [Misfire {
+            late_by: 5 * HOUR,
+            fired: false,
+        }]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_policy_picks_the_ticks_slept_through() {
+    let mut s = RecordingScheduler::new(at_hm(2, 0));
+    let skip = hourly(&mut s, "skip", MisfirePolicy::Skip);
+    let once = hourly(&mut s, "once", MisfirePolicy::FireOnce);
+    let all = hourly(&mut s, "all", MisfirePolicy::FireAll(2));
+    s.run_for(HOUR / 2).await;
+
+    // the ticks from 03:00 to 07:00 are slept through, 08:00 is on time
+    s.clock().set(at_hm(7, 30));
+    s.run_for(HOUR).await;
+
+    s.assert_fired_at("skip", [at_hm(8, 0)]);
+    s.assert_fired_at("once", [at_hm(7, 0), at_hm(8, 0)]);
+    s.assert_fired_at("all", [at_hm(6, 0), at_hm(7, 0), at_hm(8, 0)]);
+    for misfires in [skip, once, all] {
+        let late: Vec<Duration> = misfires.lock().unwrap().iter().map(|m| m.late_by).collect();
+        assert_eq!(late, [5, 4, 3, 2, 1].map(|h| h * HOUR));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_tick_on_time_is_not_a_misfire() {
+    let mut s = RecordingScheduler::new(at_hm(0, 30));
+    let misfires = hourly(&mut s, "report", MisfirePolicy::FireOnce);
+    s.run_for(3 * HOUR).await;
+
+    s.assert_fired_at("report", [at_hm(1, 0), at_hm(2, 0), at_hm(3, 0)]);
+    assert!(misfires.lock().unwrap().is_empty());
+}
+
+/// Runs on the real clock: a handler blocking the only thread of the runtime holds up the ticks of the other jobs
+#[tokio::test]
+async fn a_tick_held_up_by_a_busy_runtime_is_not_a_misfire() {
+    let mut s = Scheduler::new();
+    let misfires: Arc<Mutex<Vec<Misfire>>> = Arc::default();
+    let seen = misfires.clone();
+    s.on_misfire(move |_, misfire| seen.lock().unwrap().push(misfire.clone()));
+    static TICKS: AtomicUsize = AtomicUsize::new(0);
+    static BLOCKED: AtomicBool = AtomicBool::new(false);
+    s.add(AsyncJob::new().name("tick").cron("* * * * * *").run(|| {
+        TICKS.fetch_add(1, Ordering::SeqCst);
+        async {}
+    }))
+    .add(AsyncJob::new().name("block").cron("* * * * * *").run(|| {
+        if !BLOCKED.swap(true, Ordering::SeqCst) {
+            std::thread::sleep(Duration::from_millis(2500));
+        }
+        async {}
+    }));
+    s.run().await;
+    tokio::time::sleep(Duration::from_millis(4600)).await;
+
+    // the ticks due while the runtime was blocked start late, none of them is dropped
+    assert!(TICKS.load(Ordering::SeqCst) >= 4);
+    assert!(misfires.lock().unwrap().is_empty());
+}
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use chrono::{FixedOffset, TimeZone, Utc};
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::{RecordingScheduler, RunOutcome, RunRecord};
+
+mod common;
+
+use common::at;
+
+fn record<'a>(runs: &'a [RunRecord], job: &str) -> &'a RunRecord {
+    runs.iter()
+        .find(|record| record.run.name.as_deref() == Some(job))
+        .unwrap_or_else(|| panic!("job {} was not recorded", job))
+}
+
+async fn boom() {
+    panic!("boom")
+}
+
+#[tokio::test(start_paused = true)]
+async fn every_call_is_recorded_with_its_outcome() {
+    let mut s = RecordingScheduler::new(at(0, 30));
+    s.add(
+        AsyncJob::new()
+            .name("ok")
+            .cron("0 1 * * * *")
+            .run(|| async {}),
+    )
+    .add(
+        AsyncJob::new()
+            .name("failing")
+            .cron("0 1 * * * *")
+            .run(|| async { Err::<(), _>("the server is down") }),
+    )
+    .add(
+        AsyncJob::new()
+            .name("panicking")
+            .cron("0 1 * * * *")
+            .run(boom),
+    )
+    .add(
+        AsyncJob::new()
+            .name("slow")
+            .cron("0 1 * * * *")
+            .timeout(Duration::from_secs(5))
+            .run(|| async { tokio::time::sleep(Duration::from_secs(60)).await }),
+    )
+    .add(SyncJob::new().name("sync").cron("0 1 * * * *").run(|| {}));
+    s.run_for(Duration::from_secs(45)).await;
+
+    let runs = s.runs();
+    assert_eq!(runs.len(), 5);
+    assert!(runs
+        .iter()
+        .all(|record| record.run.scheduled_at == at(1, 0) && record.started_at == at(1, 0)));
+    assert_eq!(record(&runs, "ok").outcome, RunOutcome::Success);
+    assert_eq!(record(&runs, "sync").outcome, RunOutcome::Success);
+    assert!(matches!(
+        &record(&runs, "failing").outcome,
+        RunOutcome::Failed(err) if err.message() == "the server is down"
+    ));
+    assert_eq!(
+        record(&runs, "panicking").outcome,
+        RunOutcome::Panicked("boom".to_string())
+    );
+    let slow = record(&runs, "slow");
+    assert_eq!(slow.outcome, RunOutcome::TimedOut(Duration::from_secs(5)));
+    assert_eq!(slow.finished_at, at(1, 5));
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_times_are_recorded_in_the_timezone_of_the_scheduler() {
+    let tz = FixedOffset::east_opt(3600).unwrap();
+    let mut s = RecordingScheduler::with_tz(tz, Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    s.add(
+        AsyncJob::new()
+            .name("nine")
+            .at_time(9, 0, 0)
+            .run(|| async {}),
+    );
+    s.run_for(Duration::from_secs(2 * 24 * 3600)).await;
+
+    let fired = s.fired_at("nine");
+    assert_eq!(
+        fired,
+        [1, 2].map(|day| tz.with_ymd_and_hms(2024, 1, day, 9, 0, 0).unwrap())
+    );
+    assert!(fired.iter().all(|time| time.offset() == &tz));
+    s.assert_fired_at(
+        "nine",
+        [1, 2].map(|day| Utc.with_ymd_and_hms(2024, 1, day, 8, 0, 0).unwrap()),
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_slow_sync_handler_is_waited_for() {
+    let mut s = RecordingScheduler::new(at(0, 30));
+    s.add(
+        SyncJob::new()
+            .name("slow")
+            .cron("0 1 * * * *")
+            .run(|| std::thread::sleep(Duration::from_millis(200))),
+    );
+    s.run_for(Duration::from_secs(31)).await;
+
+    // the handler took 200ms in real time, the paused clock stood still meanwhile
+    let runs = s.runs();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].finished_at, at(1, 0));
+}
+
+#[tokio::test(start_paused = true)]
+#[should_panic(
+    expected = "job report fired at [2024-01-01T00:01:00+00:00], expected [2024-01-01T00:02:00Z]"
+)]
+async fn a_wrong_fire_time_fails_the_assertion() {
+    let mut s = RecordingScheduler::new(at(0, 30));
+    s.add(
+        AsyncJob::new()
+            .name("report")
+            .cron("0 1 * * * *")
+            .run(|| async {}),
+    );
+    s.run_for(Duration::from_secs(60)).await;
+    s.assert_fired_at("report", [at(2, 0)]);
+}
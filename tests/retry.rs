@@ -0,0 +1,97 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::RecordingScheduler;
+
+mod common;
+
+use common::{at, attempts};
+
+#[tokio::test(start_paused = true)]
+async fn the_backoff_doubles_up_to_its_max() {
+    let mut s = RecordingScheduler::new(at(0, 30));
+    let errors: Arc<Mutex<Vec<u32>>> = Arc::default();
+    let seen = errors.clone();
+    s.add(
+        AsyncJob::new()
+            .name("flaky")
+            .cron("0 1 * * * *")
+            .retry(
+                4,
+                Backoff::Exponential {
+                    base: Duration::from_secs(10),
+                    max: Duration::from_secs(25),
+                    jitter: false,
+                },
+            )
+            .on_error(move |run, _| seen.lock().unwrap().push(run.attempt))
+            .run(|| async { Err::<(), _>("the server is down") }),
+    );
+    s.run_for(Duration::from_secs(150)).await;
+
+    assert_eq!(
+        attempts(&s, "flaky"),
+        [
+            (1, at(1, 0)),
+            (2, at(1, 10)),
+            (3, at(1, 30)),
+            (4, at(1, 55))
+        ]
+    );
+    assert!(s.runs().iter().all(|run| !run.outcome.is_success()));
+    // only the last attempt is reported
+    assert_eq!(*errors.lock().unwrap(), [4]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_retry_that_succeeds_ends_the_run() {
+    static CALLS: AtomicU32 = AtomicU32::new(0);
+    let mut s = RecordingScheduler::new(at(0, 30));
+    s.add(
+        AsyncJob::new()
+            .name("second")
+            .cron("0 1 * * * *")
+            .retry(5, Backoff::Fixed(Duration::from_secs(15)))
+            .run(|| async {
+                match CALLS.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err("not yet"),
+                    _ => Ok(()),
+                }
+            }),
+    );
+    s.run_for(Duration::from_secs(150)).await;
+
+    assert_eq!(attempts(&s, "second"), [(1, at(1, 0)), (2, at(1, 15))]);
+    assert!(s.runs()[1].outcome.is_success());
+    s.assert_fired_at("second", [at(1, 0)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_jitter_waits_between_half_and_the_whole_backoff() {
+    let mut s = RecordingScheduler::new(at(0, 30));
+    s.add(
+        AsyncJob::new()
+            .name("jittered")
+            .cron("0 1 * * * *")
+            .retry(
+                2,
+                Backoff::Exponential {
+                    base: Duration::from_secs(20),
+                    max: Duration::from_secs(20),
+                    jitter: true,
+                },
+            )
+            .run(|| async { Err::<(), _>("the server is down") }),
+    );
+    s.run_for(Duration::from_secs(60)).await;
+
+    let attempts = attempts(&s, "jittered");
+    assert_eq!(attempts.len(), 2);
+    assert!(at(1, 10) <= attempts[1].1 && attempts[1].1 <= at(1, 20));
+}
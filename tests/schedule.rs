@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use chrono::{Datelike, TimeZone, Utc};
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::{MockClock, ScheduleError};
+
+fn error(job: &mut AsyncJob<()>) -> ScheduleError {
+    job.try_run::<Utc, _>(|| async {}).err().unwrap()
+}
+
+#[test]
+fn a_cron_expression_conflicts_with_other_times() {
+    let err = error(AsyncJob::new().cron("0 9 * * *").at(30.minutes()));
+    assert!(matches!(err, ScheduleError::Conflict { method: "cron" }));
+    assert_eq!(
+        err.to_string(),
+        "cron can not be combined with other times of the same schedule"
+    );
+    // other times belong to another schedule
+    assert!(AsyncJob::new()
+        .cron("0 9 * * *")
+        .and()
+        .at(30.minutes())
+        .try_run::<Utc, _>(|| async {})
+        .is_ok());
+}
+
+#[test]
+fn an_interval_conflicts_with_other_times() {
+    let err = error(
+        AsyncJob::new()
+            .every_duration(Duration::from_secs(90))
+            .at(3.hours()),
+    );
+    assert!(matches!(
+        err,
+        ScheduleError::Conflict {
+            method: "every_duration"
+        }
+    ));
+    let err = error(AsyncJob::new().with_fixed_delay(Duration::ZERO));
+    assert!(matches!(
+        err,
+        ScheduleError::ZeroDuration {
+            method: "with_fixed_delay"
+        }
+    ));
+    let err = error(AsyncJob::new().every_duration(Duration::from_secs(u64::MAX)));
+    assert_eq!(
+        err.to_string(),
+        "every_duration with a duration longer than 100 years"
+    );
+    let err = error(AsyncJob::new().with_fixed_delay(Duration::MAX));
+    assert!(matches!(
+        err,
+        ScheduleError::DurationTooLong {
+            method: "with_fixed_delay"
+        }
+    ));
+}
+
+#[test]
+fn out_of_range_values_are_reported() {
+    let err = error(AsyncJob::new().at(75.minutes()));
+    assert_eq!(err.to_string(), "minutes(75) out of range 0..59");
+}
+
+/// The next `n` run times of a crontab line, from Monday 2024-01-01
+fn crontab(expr: &str, n: usize) -> Vec<u32> {
+    let mut s = Scheduler::new();
+    s.set_clock(MockClock::new(
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+    ));
+    let job = s.add_job(AsyncJob::new().cron(expr).run(|| async {}));
+    job.upcoming(n).iter().map(|at| at.day()).collect()
+}
+
+#[test]
+fn crontab_weekdays_count_from_sunday() {
+    // Tuesday, Thursday, Saturday
+    assert_eq!(crontab("0 9 * * 2-7/2", 4), [2, 4, 6, 9]);
+    // Friday to Sunday
+    assert_eq!(crontab("0 9 * * 5-7", 4), [5, 6, 7, 12]);
+    // Sunday as 0 and as 7
+    assert_eq!(crontab("0 9 * * 0,3", 3), [3, 7, 10]);
+    assert_eq!(crontab("0 9 * * 3,7", 3), [3, 7, 10]);
+    // every other day from Sunday, and from Monday to the end of the week
+    assert_eq!(crontab("0 9 * * */2", 4), [2, 4, 6, 7]);
+    assert_eq!(crontab("0 9 * * 1/3", 3), [1, 4, 7]);
+    assert_eq!(crontab("0 9 * * MON-FRI", 6), [1, 2, 3, 4, 5, 8]);
+}
+
+#[test]
+fn crontab_days_of_month_and_week_are_not_combined() {
+    let err = error(AsyncJob::new().cron("0 0 1 * 1"));
+    assert_eq!(
+        err.to_string(),
+        "invalid cron expression 0 0 1 * 1: crontab runs on either the day of month or the day of week, give one of them as *"
+    );
+    // a day field starting with * matches every day for crontab too: the Mondays of January 1, March 11 and April 1
+    assert_eq!(crontab("0 9 */10 * 1", 3), [1, 11, 1]);
+    assert_eq!(crontab("0 9 1 * *", 2), [1, 1]);
+}
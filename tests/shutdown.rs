@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::{Clock, RecordingScheduler};
+
+#[macro_use]
+mod common;
+
+use common::at;
+
+#[tokio::test(start_paused = true)]
+async fn shutdown_waits_for_the_running_handlers() {
+    let mut s = RecordingScheduler::new(at(0, 0));
+    s.add(slow!("drained", 30));
+    s.run_for(Duration::from_secs(65)).await;
+    assert_eq!(s.jobs()[0].in_flight().len(), 1);
+
+    let report = s.shutdown(Duration::from_secs(60)).await;
+    assert!(report.is_clean());
+    let runs = s.runs();
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].run.scheduled_at, at(1, 0));
+    assert_eq!(runs[0].finished_at, at(1, 30));
+    assert!(runs[0].outcome.is_success());
+
+    // no run starts once the scheduler is shut down
+    s.run_for(Duration::from_secs(180)).await;
+    s.assert_fired_at("drained", [at(1, 0)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_queued_run_does_not_start_once_shutdown_began() {
+    let mut s = RecordingScheduler::new(at(0, 0));
+    s.add(
+        AsyncJob::new()
+            .name("queued")
+            .cron("*/10 * * * * *")
+            .concurrency(ConcurrencyPolicy::Queue(1))
+            .run(|| async { tokio::time::sleep(Duration::from_secs(20)).await }),
+    );
+    // the run of 00:10 takes until 00:30, the one of 00:20 waits behind it
+    s.run_for(Duration::from_secs(25)).await;
+    assert_eq!(s.jobs()[0].in_flight().len(), 1);
+
+    let report = s.shutdown(Duration::from_secs(60)).await;
+    assert!(report.is_clean());
+    assert_eq!(s.clock().now(), at(0, 30));
+    s.run_for(Duration::from_secs(60)).await;
+    s.assert_fired_at("queued", [at(0, 10)]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn shutdown_gives_up_on_handlers_after_the_timeout() {
+    let mut s = RecordingScheduler::new(at(0, 0));
+    s.add(slow!("stuck", 600));
+    s.run_for(Duration::from_secs(65)).await;
+
+    let report = s.shutdown(Duration::from_secs(10)).await;
+    assert!(!report.is_clean());
+    assert_eq!(report.aborted.len(), 1);
+    assert_eq!(report.aborted[0].scheduled_at, at(1, 0));
+    assert_eq!(s.clock().now(), at(1, 15));
+    assert!(s.runs().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn jobs_added_after_shutdown_are_cancelled() {
+    let mut s = RecordingScheduler::new(at(0, 0));
+    let kept = s.add_job(slow!("kept", 1));
+    s.run_for(Duration::from_secs(1)).await;
+    s.shutdown(Duration::from_secs(1)).await;
+
+    let late = s.add_job(slow!("late", 1));
+    assert_eq!(late.status(), JobStatus::Cancelled);
+    let handle = s.handle();
+    assert_eq!(handle.add(slow!("later", 1)).status(), JobStatus::Cancelled);
+    assert!(handle.replace(kept.id(), slow!("replaced", 1)).is_none());
+    assert_eq!(s.jobs().len(), 1);
+
+    s.run_for(Duration::from_secs(180)).await;
+    assert!(s.runs().is_empty());
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use chrono::{NaiveDate, Utc};
+use tokio_easy_timer::{
+    DstPolicy, HandlerRegistry, JobOptions, JobScheduleBuilder, JobSpec, JobStore,
+    RecordingScheduler, ScheduleError, SpecError, SqliteStore,
+};
+
+mod common;
+
+use common::at;
+
+fn registry() -> HandlerRegistry {
+    let mut registry = HandlerRegistry::new();
+    registry.register("ping", || async {});
+    registry
+}
+
+fn spec(json: &str) -> JobSpec {
+    serde_json::from_str(json).unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_whole_schedule_of_a_spec_is_built() {
+    let registry = registry();
+    let mut s = RecordingScheduler::new(at(0, 0));
+    for json in [
+        r#"{ "name": "poll", "every": 90, "handler": "ping" }"#,
+        r#"{ "name": "later", "cron": "0 * * * * *", "since": "2024-01-01T00:02:30", "handler": "ping" }"#,
+        r#"{ "name": "delayed", "cron": "0 * * * * *", "delay": 150, "handler": "ping" }"#,
+        r#"{ "name": "twice", "cron": "0 */2 * * * *", "repeat": 2, "interval": 10, "handler": "ping" }"#,
+    ] {
+        s.add(registry.build(&spec(json)).unwrap());
+    }
+    s.run_for(Duration::from_secs(290)).await;
+
+    s.assert_fired_at("poll", [at(1, 30), at(3, 0), at(4, 30)]);
+    s.assert_fired_at("later", [at(3, 0), at(4, 0)]);
+    s.assert_fired_at("delayed", [at(3, 0), at(4, 0)]);
+    let twice: Vec<_> = s
+        .runs()
+        .iter()
+        .filter(|record| record.run.name.as_deref() == Some("twice"))
+        .map(|record| record.started_at)
+        .collect();
+    assert_eq!(twice, [at(2, 0), at(2, 10), at(4, 0), at(4, 10)]);
+}
+
+#[test]
+fn a_spec_with_both_a_cron_and_a_period_is_refused() {
+    let err = registry()
+        .build::<Utc>(&spec(
+            r#"{ "name": "both", "cron": "0 * * * * *", "every": 90, "handler": "ping" }"#,
+        ))
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        SpecError::InvalidSchedule {
+            error: ScheduleError::Conflict {
+                method: "every_duration"
+            },
+            ..
+        }
+    ));
+    let err = registry()
+        .build::<Utc>(&spec(r#"{ "name": "none", "handler": "ping" }"#))
+        .err()
+        .unwrap();
+    assert!(matches!(err, SpecError::InvalidCron { .. }));
+}
+
+#[test]
+fn the_stores_keep_the_whole_spec() {
+    let spec = JobSpec {
+        every: Some(Duration::from_millis(1500)),
+        fixed_delay: None,
+        interval: Duration::from_secs(10),
+        since: Some(
+            NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(8, 0, 0)
+                .unwrap(),
+        ),
+        delay: Duration::from_secs(30),
+        dst: vec![DstPolicy::RunBoth, DstPolicy::Skip],
+        ..JobSpec::new("poll", "", "ping")
+    };
+    let json = serde_json::to_string(&spec).unwrap();
+    assert_eq!(serde_json::from_str::<JobSpec>(&json).unwrap(), spec);
+
+    let store = SqliteStore::open_in_memory().unwrap();
+    store.save_job(&spec).unwrap();
+    assert_eq!(store.jobs().unwrap(), [spec]);
+}
+
+#[test]
+fn schedule_builders_and_job_options_are_serialized() {
+    let mut builder = JobScheduleBuilder::new();
+    builder.every_duration(Duration::from_secs(90));
+    let json = serde_json::to_value(&builder).unwrap();
+    assert_eq!(json["fixed_rate"], 90.0);
+    let back: JobScheduleBuilder = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&back).unwrap(), json);
+
+    let options: JobOptions =
+        serde_json::from_str(r#"{ "name": "backup", "tags": ["db"], "timeout": 30 }"#).unwrap();
+    let json = serde_json::to_value(&options).unwrap();
+    assert_eq!(json["name"], "backup");
+    assert_eq!(json["tags"], serde_json::json!(["db"]));
+    assert_eq!(json["timeout"], 30.0);
+}
@@ -0,0 +1,28 @@
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::{JobStore, MemoryStore, RecordingScheduler};
+
+#[macro_use]
+mod common;
+
+use common::{at_hm, HOUR};
+
+#[tokio::test(start_paused = true)]
+async fn the_runs_missed_while_down_are_caught_up_on_start() {
+    let store = MemoryStore::new();
+    for job in ["skip", "once", "all"] {
+        store.set_last_run(job, at_hm(2, 0)).unwrap();
+    }
+    let mut s = RecordingScheduler::new(at_hm(5, 30));
+    s.set_store(store);
+    s.add(hourly!("skip", MisfirePolicy::Skip))
+        .add(hourly!("once", MisfirePolicy::FireOnce))
+        .add(hourly!("all", MisfirePolicy::FireAll(10)))
+        .add(hourly!("new", MisfirePolicy::FireAll(10)));
+    s.run_for(HOUR).await;
+
+    s.assert_fired_at("skip", [at_hm(6, 0)]);
+    s.assert_fired_at("once", [at_hm(5, 0), at_hm(6, 0)]);
+    s.assert_fired_at("all", [at_hm(3, 0), at_hm(4, 0), at_hm(5, 0), at_hm(6, 0)]);
+    // a job the store does not know has nothing to catch up
+    s.assert_fired_at("new", [at_hm(6, 0)]);
+}
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::RecordingScheduler;
+
+#[tokio::test(start_paused = true)]
+async fn cancel_by_tag_removes_the_jobs_and_counts_them_once() {
+    let mut s = RecordingScheduler::new(Utc.with_ymd_and_hms(2024, 1, 1, 0, 30, 0).unwrap());
+    for name in ["a", "b"] {
+        s.add(
+            AsyncJob::new()
+                .name(name)
+                .tag("billing")
+                .cron("0 * * * *")
+                .run(|| async {}),
+        );
+    }
+    let other = s.add_job(AsyncJob::new().name("c").cron("0 * * * *").run(|| async {}));
+    s.find_by_tag("billing")[0].cancel();
+
+    assert_eq!(s.cancel_by_tag("billing"), 1);
+    assert_eq!(s.cancel_by_tag("billing"), 0);
+    assert_eq!(
+        s.jobs().iter().map(|job| job.id()).collect::<Vec<_>>(),
+        [other.id()]
+    );
+
+    s.run_for(Duration::from_secs(3600)).await;
+    s.assert_fired_at("a", Vec::<chrono::DateTime<Utc>>::new());
+    s.assert_fired_at("b", Vec::<chrono::DateTime<Utc>>::new());
+    s.assert_fired_at("c", [Utc.with_ymd_and_hms(2024, 1, 1, 1, 0, 0).unwrap()]);
+}
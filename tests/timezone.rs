@@ -0,0 +1,83 @@
+use chrono::{FixedOffset, Utc};
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::{JobTz, RecordingScheduler, ScheduleError};
+
+mod common;
+
+use common::{utc, DAY};
+
+#[tokio::test(start_paused = true)]
+async fn every_job_runs_in_its_own_timezone() {
+    let tokyo = FixedOffset::east_opt(9 * 3600).unwrap();
+    let mut s = RecordingScheduler::with_tz(tokyo, utc(1, 1, 12, 0));
+    s.add(
+        AsyncJob::new()
+            .name("tokyo")
+            .at_time(9, 0, 0)
+            .run(|| async {}),
+    )
+    .add(
+        AsyncJob::new()
+            .name("london")
+            .timezone(Utc)
+            .at_time(9, 0, 0)
+            .run(|| async {}),
+    )
+    .add(
+        AsyncJob::new()
+            .name("new-york")
+            .timezone_name("-05:00")
+            .at_time(9, 0, 0)
+            .run(|| async {}),
+    );
+    s.run_for(2 * DAY).await;
+
+    s.assert_fired_at("tokyo", [utc(1, 2, 0, 0), utc(1, 3, 0, 0)]);
+    s.assert_fired_at("london", [utc(1, 2, 9, 0), utc(1, 3, 9, 0)]);
+    s.assert_fired_at("new-york", [utc(1, 1, 14, 0), utc(1, 2, 14, 0)]);
+    // the scheduled time is given in the timezone of the job
+    let offsets: Vec<String> = s
+        .fired_at("new-york")
+        .iter()
+        .map(|t| t.offset().to_string())
+        .collect();
+    assert_eq!(offsets, ["-05:00", "-05:00"]);
+}
+
+#[test]
+fn timezone_names_are_parsed() {
+    let names = ["UTC", "local", "+09:00", "-0530"];
+    let parsed: Vec<JobTz> = names.iter().map(|name| name.parse().unwrap()).collect();
+    assert_eq!(
+        parsed,
+        [
+            JobTz::Utc,
+            JobTz::Local,
+            JobTz::Fixed(FixedOffset::east_opt(9 * 3600).unwrap()),
+            JobTz::Fixed(FixedOffset::west_opt(5 * 3600 + 30 * 60).unwrap()),
+        ]
+    );
+    for name in ["+9", "+24:00", "Mars/Olympus_Mons", ""] {
+        assert_eq!(
+            name.parse::<JobTz>(),
+            Err(ScheduleError::UnknownTimezone(name.to_string()))
+        );
+    }
+}
+
+#[test]
+fn an_unknown_timezone_name_is_returned_by_try_run() {
+    let err = AsyncJob::new()
+        .timezone_name("Mars/Olympus_Mons")
+        .at_time(9, 0, 0)
+        .try_run::<Utc, _>(|| async {})
+        .err()
+        .unwrap();
+    assert_eq!(
+        err,
+        ScheduleError::UnknownTimezone("Mars/Olympus_Mons".to_string())
+    );
+    assert!(err
+        .to_string()
+        .starts_with("unknown timezone Mars/Olympus_Mons"));
+}
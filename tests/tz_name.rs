@@ -0,0 +1,51 @@
+use tokio_easy_timer::prelude::*;
+use tokio_easy_timer::{JobTz, RecordingScheduler, ScheduleError};
+
+mod common;
+
+use common::{utc, DAY};
+
+#[tokio::test(start_paused = true)]
+async fn a_named_timezone_follows_daylight_saving() {
+    let berlin: chrono_tz::Tz = "Europe/Berlin".parse().unwrap();
+    let mut s = RecordingScheduler::with_tz(berlin, utc(3, 30, 12, 0));
+    s.add(
+        AsyncJob::new()
+            .name("nine")
+            .at_time(9, 0, 0)
+            .run(|| async {}),
+    )
+    .add(
+        AsyncJob::new()
+            .name("new-york")
+            .timezone_name("America/New_York")
+            .at_time(9, 0, 0)
+            .run(|| async {}),
+    );
+    s.run_for(2 * DAY).await;
+
+    // Berlin moves to summer time on March 31, New York has done so on March 10
+    s.assert_fired_at("nine", [utc(3, 31, 7, 0), utc(4, 1, 7, 0)]);
+    s.assert_fired_at("new-york", [utc(3, 30, 13, 0), utc(3, 31, 13, 0)]);
+}
+
+#[test]
+fn a_scheduler_is_created_from_a_timezone_name() {
+    assert!(Scheduler::with_tz_name("America/New_York").is_ok());
+    assert_eq!(
+        Scheduler::with_tz_name("Europe/Nowhere").err(),
+        Some(ScheduleError::UnknownTimezone("Europe/Nowhere".to_string()))
+    );
+}
+
+#[test]
+fn iana_names_are_parsed() {
+    assert_eq!(
+        "Europe/Berlin".parse::<JobTz>(),
+        Ok(JobTz::Named(chrono_tz::Europe::Berlin))
+    );
+    assert_eq!(
+        "Europe/Nowhere".parse::<JobTz>(),
+        Err(ScheduleError::UnknownTimezone("Europe/Nowhere".to_string()))
+    );
+}
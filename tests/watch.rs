@@ -0,0 +1,84 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use tokio_easy_timer::{HandlerRegistry, JobHandle, JobStatus, RecordingScheduler};
+
+mod common;
+
+use common::at;
+
+/// A config file of its own for every test
+fn config(test: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "tokio-easy-timer-{}-{}.json",
+        test,
+        std::process::id()
+    ))
+}
+
+/// Write the jobs given as `(name, cron)`, every job calls the `ping` handler
+fn write(path: &PathBuf, jobs: &[(&str, &str)]) {
+    let jobs: Vec<String> = jobs
+        .iter()
+        .map(|(name, cron)| {
+            format!(
+                r#"{{ "name": "{}", "cron": "{}", "handler": "ping" }}"#,
+                name, cron
+            )
+        })
+        .collect();
+    fs::write(path, format!(r#"{{ "jobs": [{}] }}"#, jobs.join(", "))).unwrap();
+}
+
+fn find(s: &RecordingScheduler, name: &str) -> Option<JobHandle> {
+    s.jobs().into_iter().find(|job| job.name() == Some(name))
+}
+
+#[tokio::test(start_paused = true)]
+async fn only_the_jobs_that_changed_are_touched() {
+    let path = config("diff");
+    write(
+        &path,
+        &[
+            ("keep", "0 * * * * *"),
+            ("change", "0 */2 * * * *"),
+            ("drop", "0 * * * * *"),
+        ],
+    );
+    let mut registry = HandlerRegistry::new();
+    registry.register("ping", || async {});
+    let mut s = RecordingScheduler::new(at(0, 30));
+    s.watch_config(&path, registry).unwrap();
+    s.run_for(Duration::from_secs(75)).await;
+    let keep = find(&s, "keep").unwrap();
+    let change = find(&s, "change").unwrap();
+
+    write(
+        &path,
+        &[
+            ("keep", "0 * * * * *"),
+            ("change", "30 * * * * *"),
+            ("new", "0 * * * * *"),
+        ],
+    );
+    s.run_for(Duration::from_secs(60)).await;
+
+    // the changed job is replaced under the same id, the unchanged one keeps running
+    assert_eq!(keep.status(), JobStatus::Running);
+    assert_eq!(change.status(), JobStatus::Cancelled);
+    assert_eq!(find(&s, "change").unwrap().id(), change.id());
+    assert!(find(&s, "drop").is_none());
+    assert_eq!(s.jobs().len(), 3);
+    s.assert_fired_at("keep", [at(1, 0), at(2, 0)]);
+    s.assert_fired_at("change", [at(2, 30)]);
+    s.assert_fired_at("drop", [at(1, 0)]);
+    s.assert_fired_at("new", [at(2, 0)]);
+
+    // a file that can not be loaded leaves the jobs as they are
+    write(&path, &[("keep", "0 * * * * *"), ("broken", "not cron")]);
+    s.run_for(Duration::from_secs(60)).await;
+    assert_eq!(s.jobs().len(), 3);
+    s.assert_fired_at("keep", [at(1, 0), at(2, 0), at(3, 0)]);
+    s.assert_fired_at("new", [at(2, 0), at(3, 0)]);
+
+    fs::remove_file(&path).unwrap();
+}